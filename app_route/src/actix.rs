@@ -0,0 +1,140 @@
+//! [actix-web](https://docs.rs/actix-web) integration, enabled with the
+//! `actix-web` feature.
+//!
+//! Derived routes implement `FromRequest`, so they can be used as handler
+//! arguments directly, and [`resource`] creates a `Resource` for a route's
+//! paths.
+//!
+//! ```ignore
+//! use actix_web::{web, App};
+//!
+//! async fn show_user(route: UserRoute) -> String {
+//!     format!("User {}", route.user_id)
+//! }
+//!
+//! let app = App::new().service(app_route::actix::resource::<UserRoute>().route(web::get().to(show_user)));
+//! ```
+//!
+//! Requests are checked against `ParseLimits::untrusted()`. Other limits
+//! can be set with `App::app_data(limits)`.
+
+use crate::{uri, AppRoute, ParseLimits, RouteParseErr};
+use actix_web::{
+	http::{header, StatusCode},
+	HttpRequest, HttpResponse, ResponseError,
+};
+
+#[doc(hidden)]
+pub use actix_web::{dev::Payload, FromRequest, HttpRequest as Request};
+
+/// The error of a derived route's extractor, which responds with
+/// `RouteParseErr::status_code()`.
+#[derive(Debug)]
+pub struct RouteRejection(pub RouteParseErr);
+
+impl std::fmt::Display for RouteRejection {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		self.0.fmt(f)
+	}
+}
+
+impl ResponseError for RouteRejection {
+	fn status_code(&self) -> StatusCode {
+		StatusCode::from_u16(self.0.status_code().as_u16()).expect("a valid status code")
+	}
+
+	fn error_response(&self) -> HttpResponse {
+		let mut response = HttpResponse::build(ResponseError::status_code(self));
+
+		if let RouteParseErr::MethodNotAllowed { ref allowed } = self.0 {
+			response.insert_header((header::ALLOW, allowed.join(", ")));
+		}
+
+		response.body(self.0.to_string())
+	}
+}
+
+/// Parses a route from a request, for the generated `FromRequest`
+/// implementations. The host is taken from the request's URI or its `Host`
+/// header. The request is checked against the `ParseLimits` in the app
+/// data, or `ParseLimits::untrusted()` if there are none.
+#[doc(hidden)]
+pub fn from_request<R: AppRoute>(request: &HttpRequest) -> Result<R, RouteRejection> {
+	let untrusted;
+	let limits = match request.app_data::<ParseLimits>() {
+		Some(limits) => limits,
+		None => {
+			untrusted = ParseLimits::untrusted();
+			&untrusted
+		}
+	};
+
+	let host_header = request
+		.headers()
+		.get(header::HOST)
+		.and_then(|host| host.to_str().ok());
+
+	uri::from_request_parts(
+		request.method().as_str(),
+		request.uri().host(),
+		host_header,
+		request.uri().path_and_query().map(|p| p.as_str()),
+		limits,
+	)
+	.map_err(RouteRejection)
+}
+
+/// Translates a route pattern such as `/users/:user_id/files/:path*` into
+/// actix-web's path syntax, `/users/{user_id}/files/{path:.*}`.
+///
+/// actix-web has no escape for literal braces, so each literal `{` or `}` is
+/// matched by a param whose regex is `\x7B` or `\x7D`.
+pub fn actix_path(pattern: &str) -> String {
+	let mut path = String::with_capacity(pattern.len());
+	let mut name: Option<String> = None;
+	let mut braces = 0;
+
+	for c in pattern.chars() {
+		match name {
+			Some(ref mut param) if c == '/' => {
+				path.push_str(&format!("{{{}}}/", param));
+				name = None;
+			}
+			Some(ref mut param) if c == '*' => {
+				path.push_str(&format!("{{{}:.*}}", param));
+				name = None;
+			}
+			Some(ref mut param) => param.push(c),
+			None if c == ':' => name = Some(String::new()),
+			None if c == '{' || c == '}' => {
+				let escaped = if c == '{' { "\\x7B" } else { "\\x7D" };
+				path.push_str(&format!("{{app_route_brace{}:{}}}", braces, escaped));
+				braces += 1;
+			}
+			None => path.push(c),
+		}
+	}
+
+	if let Some(param) = name {
+		path.push_str(&format!("{{{}}}", param));
+	}
+
+	path
+}
+
+/// Creates an actix-web `Resource` matching every path declared by `R`.
+/// Routes which share a path, such as the variants of an enum router which
+/// differ by method, are only listed once.
+pub fn resource<R: AppRoute>() -> actix_web::Resource {
+	let mut paths: Vec<String> = Vec::new();
+
+	for route in R::routes() {
+		let path = actix_path(&route.pattern);
+
+		if !paths.contains(&path) {
+			paths.push(path);
+		}
+	}
+
+	actix_web::Resource::new(paths)
+}
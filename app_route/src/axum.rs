@@ -0,0 +1,134 @@
+//! [axum](https://docs.rs/axum) integration, enabled with the `axum`
+//! feature.
+//!
+//! Derived routes implement `FromRequestParts`, so they can be used as
+//! handler arguments directly, and [`RouterExt::app_route`] registers a
+//! route's paths on a `Router`.
+//!
+//! ```ignore
+//! use app_route::axum::RouterExt;
+//! use axum::{routing::get, Router};
+//!
+//! async fn show_user(route: UserRoute) -> String {
+//!     format!("User {}", route.user_id)
+//! }
+//!
+//! let app: Router = Router::new().app_route::<UserRoute>(get(show_user));
+//! ```
+//!
+//! Requests are checked against `ParseLimits::untrusted()`. Other limits
+//! can be set by adding them to the request extensions, with
+//! `.layer(Extension(limits))`.
+
+use crate::{AppRoute, ParseLimits, RouteParseErr};
+use ::axum::{
+	http::{header, HeaderValue},
+	response::{IntoResponse, Response},
+	routing::MethodRouter,
+	Router,
+};
+
+#[doc(hidden)]
+pub use ::axum::extract::FromRequestParts;
+
+/// The rejection of a derived route's extractor, which responds with
+/// `RouteParseErr::status_code()`.
+#[derive(Debug)]
+pub struct RouteRejection(pub RouteParseErr);
+
+impl IntoResponse for RouteRejection {
+	fn into_response(self) -> Response {
+		let mut response = (self.0.status_code(), self.0.to_string()).into_response();
+
+		if let RouteParseErr::MethodNotAllowed { ref allowed } = self.0 {
+			if let Ok(allow) = HeaderValue::from_str(&allowed.join(", ")) {
+				response.headers_mut().insert(header::ALLOW, allow);
+			}
+		}
+
+		response
+	}
+}
+
+/// Parses a route from a request, for the generated `FromRequestParts`
+/// implementations. The request is checked against the `ParseLimits` in
+/// its extensions, or `ParseLimits::untrusted()` if there are none.
+#[doc(hidden)]
+pub fn from_request_parts<R: AppRoute>(
+	parts: &::axum::http::request::Parts,
+) -> Result<R, RouteRejection> {
+	let untrusted;
+	let limits = match parts.extensions.get::<ParseLimits>() {
+		Some(limits) => limits,
+		None => {
+			untrusted = ParseLimits::untrusted();
+			&untrusted
+		}
+	};
+
+	crate::uri::from_http(&parts.method, &parts.uri, &parts.headers, limits).map_err(RouteRejection)
+}
+
+/// Translates a route pattern such as `/users/:user_id/files/:path*` into
+/// axum's path syntax, `/users/{user_id}/files/{*path}`.
+///
+/// axum wildcards have to span whole segments and never match an empty
+/// path, so `/files/:path*` doesn't match `/files/` once registered.
+///
+/// # Panics
+///
+/// Panics for a param or wildcard which doesn't start a segment, such as
+/// the constrained `/users/v:version` or `/users:tail*`, as axum can't
+/// express them.
+pub fn axum_path(pattern: &str) -> String {
+	pattern
+		.split('/')
+		.map(|segment| {
+			if let Some(name) = segment.strip_prefix(':') {
+				match name.strip_suffix('*') {
+					Some(name) => format!("{{*{}}}", name),
+					None => format!("{{{}}}", name),
+				}
+			} else if segment.contains(':') {
+				panic!(
+					"The route `{}` has a param which doesn't start a segment (`{}`), which axum can't match",
+					pattern, segment
+				);
+			} else {
+				segment.replace('{', "{{").replace('}', "}}")
+			}
+		})
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+/// Registers derived routes on an axum `Router`.
+pub trait RouterExt<S> {
+	/// Routes every path declared by `R` to `method_router`. Routes which
+	/// share a path, such as the variants of an enum router which differ by
+	/// method, are registered once.
+	fn app_route<R: AppRoute>(self, method_router: MethodRouter<S>) -> Self;
+}
+
+impl<S> RouterExt<S> for Router<S>
+where
+	S: Clone + Send + Sync + 'static,
+{
+	fn app_route<R: AppRoute>(mut self, method_router: MethodRouter<S>) -> Self {
+		let mut paths: Vec<String> = Vec::new();
+
+		for route in R::routes() {
+			let path = axum_path(&route.pattern);
+
+			if !paths.contains(&path) {
+				paths.push(path);
+			}
+		}
+
+		for path in paths {
+			self = self.route(&path, method_router.clone());
+		}
+
+		self
+	}
+}
@@ -0,0 +1,43 @@
+//! Support for the builders generated with `#[app_route(builder)]` and
+//! `#[derive(QueryBuilder)]`.
+//!
+//! ```ignore
+//! #[derive(Debug, Default, PartialEq, Serialize, Deserialize, QueryBuilder)]
+//! struct UserListQuery {
+//!     limit: Option<u64>,
+//!     offset: Option<u64>,
+//!     keyword: Option<String>,
+//! }
+//!
+//! #[derive(AppRoute, Clone, Debug, PartialEq)]
+//! #[route("/groups/:group_id/users")]
+//! #[app_route(builder)]
+//! struct UsersListRoute {
+//!     group_id: u64,
+//!
+//!     #[query]
+//!     query: UserListQuery,
+//! }
+//!
+//! let route = UsersListRoute::builder(1).limit(20).keyword("x").build();
+//! let next = route.with_query(|q: &mut UserListQuery| q.offset = Some(20));
+//! ```
+
+/// Gives mutable access to a query of type `Q`. Implemented for routes with
+/// `#[app_route(builder)]` and their builders, once per `#[query]` field
+/// type, and for every `#[derive(QueryBuilder)]` type itself.
+///
+/// `Option` query fields are filled in with `Q::default()` when accessed.
+pub trait QueryMut<Q> {
+	fn query_mut(&mut self) -> &mut Q;
+
+	/// Clones the route with a modified query, e.g. for pagination links.
+	fn with_query<F: FnOnce(&mut Q)>(&self, modify: F) -> Self
+	where
+		Self: Clone,
+	{
+		let mut route = self.clone();
+		modify(route.query_mut());
+		route
+	}
+}
@@ -0,0 +1,183 @@
+//! A typed HTTP client built on [reqwest](https://docs.rs/reqwest), enabled
+//! with the `reqwest` feature.
+//!
+//! Routes declare their method and JSON body types in `#[route]`, so the
+//! same definitions can be shared between a server and its clients:
+//!
+//! ```ignore
+//! #[derive(AppRoute)]
+//! #[route("/users", method = "POST", request = "NewUser", response = "User")]
+//! struct CreateUserRoute {}
+//!
+//! let client = Client::new("https://example.com/api/".parse().unwrap());
+//! let user: User = client.send(&CreateUserRoute {}, &new_user).await?;
+//! ```
+//!
+//! reqwest is used without its default features, so `Client::new` can't
+//! make `https` requests until a TLS feature such as `rustls-tls` or
+//! `default-tls` is enabled on reqwest in the application's own
+//! `Cargo.toml`.
+
+use crate::AppRoute;
+use reqwest::{header, Method, RequestBuilder, StatusCode, Url};
+use serde::{
+	de::{self, DeserializeOwned, Visitor},
+	Deserialize, Deserializer, Serialize,
+};
+
+/// The method and body types of a route, implemented by the derive with the
+/// `reqwest` feature.
+///
+/// Routes without a `request` type send no body, and routes without a
+/// `response` type ignore the response body.
+pub trait Endpoint: AppRoute {
+	/// The first `method` of the route, or `GET` if it has none.
+	const METHOD: &'static str;
+
+	/// Serialized as the JSON request body, unless it serializes to `null`.
+	type Request: Serialize;
+
+	/// Deserialized from the JSON response body. An empty body is read as
+	/// `null`. The body is ignored if this is `()` or a unit struct.
+	type Response: DeserializeOwned;
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+	/// The request couldn't be sent, or the response couldn't be read.
+	Request(reqwest::Error),
+
+	/// The server responded with an error status, and this body.
+	Status(StatusCode, String),
+
+	/// The request or response body isn't valid JSON for its type.
+	Json(serde_json::Error),
+}
+
+impl std::fmt::Display for ClientError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{:?}", self)
+	}
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+	fn from(error: reqwest::Error) -> Self {
+		ClientError::Request(error)
+	}
+}
+
+impl From<serde_json::Error> for ClientError {
+	fn from(error: serde_json::Error) -> Self {
+		ClientError::Json(error)
+	}
+}
+
+/// Sends routes to a server under a base URL.
+#[derive(Clone, Debug)]
+pub struct Client {
+	http: reqwest::Client,
+	base: Url,
+}
+
+impl Client {
+	/// Creates a client for the server at `base`. The path of `base` is kept
+	/// as a prefix of every route, as in `AppRoute::to_url`.
+	pub fn new(base: Url) -> Self {
+		Self::with_client(reqwest::Client::new(), base)
+	}
+
+	/// Creates a client using an existing `reqwest::Client`.
+	pub fn with_client(http: reqwest::Client, base: Url) -> Self {
+		Client { http, base }
+	}
+
+	/// Builds the request for `route` without sending it, for adding headers
+	/// such as authentication.
+	pub fn request<R: Endpoint>(
+		&self,
+		route: &R,
+		body: &R::Request,
+	) -> Result<RequestBuilder, ClientError> {
+		let method = Method::from_bytes(R::METHOD.as_bytes()).expect("route has a valid method");
+		let request = self.http.request(method, route.to_url(&self.base));
+
+		let body = serde_json::to_vec(body)?;
+
+		if body == b"null" {
+			Ok(request)
+		} else {
+			Ok(request
+				.header(header::CONTENT_TYPE, "application/json")
+				.body(body))
+		}
+	}
+
+	/// Sends `route` with `body`, and deserializes the response.
+	pub async fn send<R: Endpoint>(
+		&self,
+		route: &R,
+		body: &R::Request,
+	) -> Result<R::Response, ClientError> {
+		self.send_request::<R>(self.request(route, body)?).await
+	}
+
+	/// Sends a request built by [`Client::request`], and deserializes the
+	/// response.
+	pub async fn send_request<R: Endpoint>(
+		&self,
+		request: RequestBuilder,
+	) -> Result<R::Response, ClientError> {
+		let response = request.send().await?;
+		let status = response.status();
+		let body = response.bytes().await?;
+
+		if !status.is_success() {
+			return Err(ClientError::Status(
+				status,
+				String::from_utf8_lossy(&body).into_owned(),
+			));
+		}
+
+		if let Ok(unit) = R::Response::deserialize(UnitProbe) {
+			return Ok(unit);
+		}
+
+		if body.is_empty() {
+			Ok(serde_json::from_slice(b"null")?)
+		} else {
+			Ok(serde_json::from_slice(&body)?)
+		}
+	}
+}
+
+/// A `Deserializer` which only produces unit values, to find out whether a
+/// response type is `()` or a unit struct without reading the body.
+struct UnitProbe;
+
+impl<'de> Deserializer<'de> for UnitProbe {
+	type Error = de::value::Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+		Err(de::Error::custom("not a unit type"))
+	}
+
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		visitor.visit_unit()
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		visitor.visit_unit()
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf option newtype_struct seq tuple tuple_struct map struct
+		enum identifier ignored_any
+	}
+}
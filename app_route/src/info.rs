@@ -0,0 +1,85 @@
+//! Metadata about the routes a type declares, for registering them with web
+//! frameworks and generating documentation.
+
+/// A single `#[route]` declaration, as returned by `AppRoute::routes()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteInfo {
+	/// The path pattern as written in `#[route]`, including the prefixes it
+	/// is mounted under, e.g. `/orgs/:org_id/users/:user_id`.
+	pub pattern: String,
+
+	/// The host pattern, e.g. `:tenant.example.com`.
+	pub host: Option<String>,
+
+	/// The methods the route accepts. Empty if it accepts any method.
+	pub methods: Vec<String>,
+
+	/// The top-level keys of the route's `#[query]` fields, in order.
+	pub query: Vec<String>,
+}
+
+impl RouteInfo {
+	/// Mounts the route under a `#[route_prefix]` or the route of a struct
+	/// with a `#[nested]` field. The host of the parent takes precedence,
+	/// and only methods accepted by both the parent and the route are kept.
+	pub fn mount(mut self, prefix: &str, host: Option<&str>, methods: &[&str]) -> RouteInfo {
+		self.pattern = format!("{}{}", prefix, self.pattern);
+
+		if let Some(host) = host {
+			self.host = Some(host.to_string());
+		}
+
+		if !methods.is_empty() {
+			if self.methods.is_empty() {
+				self.methods = methods.iter().map(|m| m.to_string()).collect();
+			} else {
+				self.methods.retain(|m| methods.contains(&m.as_str()));
+			}
+		}
+
+		self
+	}
+
+	/// Renders the route as an [RFC 6570](https://tools.ietf.org/html/rfc6570)
+	/// URI template for hypermedia links, e.g.
+	/// `/groups/{group_id}/users{?limit,offset}`. Wildcard params use
+	/// reserved expansion, `{+path}`, so their slashes are kept.
+	pub fn uri_template(&self) -> String {
+		let mut template = String::with_capacity(self.pattern.len());
+		let mut name: Option<String> = None;
+
+		for c in self.pattern.chars() {
+			match name {
+				Some(ref param) if c == '*' => {
+					template.push_str(&format!("{{+{}}}", param));
+					name = None;
+				}
+				Some(ref param) if c == '/' => {
+					template.push_str(&format!("{{{}}}/", param));
+					name = None;
+				}
+				Some(ref mut param) => param.push(c),
+				None if c == ':' => name = Some(String::new()),
+				None => template.push(c),
+			}
+		}
+
+		if let Some(param) = name {
+			template.push_str(&format!("{{{}}}", param));
+		}
+
+		let mut query: Vec<&str> = Vec::new();
+
+		for key in &self.query {
+			if !query.contains(&key.as_str()) {
+				query.push(key);
+			}
+		}
+
+		if !query.is_empty() {
+			template.push_str(&format!("{{?{}}}", query.join(",")));
+		}
+
+		template
+	}
+}
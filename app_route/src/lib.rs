@@ -15,47 +15,47 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct UserListQuery {
-	limit: Option<u64>,
-	offset: Option<u64>,
-	keyword: Option<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    keyword: Option<String>,
 
-	#[serde(default)]
-	friends_only: bool,
+    #[serde(default)]
+    friends_only: bool,
 }
 
 #[derive(AppRoute, Debug, PartialEq)]
 #[route("/groups/:group_id/users")]
 struct UsersListRoute {
-	group_id: u64,
+    group_id: u64,
 
-	#[query]
-	query: UserListQuery,
+    #[query]
+    query: UserListQuery,
 }
 
 fn main() {
-	let path: UsersListRoute =
-		"/groups/4313145/users?offset=10&limit=20&friends_only=false&keyword=some_keyword"
-			.parse()
-			.unwrap();
-
-	assert_eq!(
-		path,
-		UsersListRoute {
-			group_id: 4313145,
-			query: {
-				UserListQuery {
-					limit: Some(20),
-					offset: Some(10),
-					keyword: Some("some_keyword".to_string()),
-					friends_only: false,
-				}
-			}
-		}
-	);
+    let path: UsersListRoute =
+        "/groups/4313145/users?offset=10&limit=20&friends_only=false&keyword=some_keyword"
+            .parse()
+            .unwrap();
 
-	println!("Path: {}", path);
-	// Output:
-	// Path: /groups/4313145/users?limit=20&offset=10&keyword=some_keyword&friends_only=false
+    assert_eq!(
+        path,
+        UsersListRoute {
+            group_id: 4313145,
+            query: {
+                UserListQuery {
+                    limit: Some(20),
+                    offset: Some(10),
+                    keyword: Some("some_keyword".to_string()),
+                    friends_only: false,
+                }
+            }
+        }
+    );
+
+    println!("Path: {}", path);
+    // Output:
+    // Path: /groups/4313145/users?limit=20&offset=10&keyword=some_keyword&friends_only=false
 }
 ```
 */
@@ -66,10 +66,48 @@ pub use lazy_static::lazy_static;
 #[doc(hidden)]
 pub use regex::Regex;
 
+#[doc(hidden)]
+pub use serde;
+
 #[doc(hidden)]
 pub use serde_qs;
 
-pub use app_route_derive::AppRoute;
+#[cfg(feature = "http")]
+pub use http;
+
+#[cfg(feature = "url")]
+pub use url;
+
+pub use app_route_derive::{AppRoute, Pagination, QueryBuilder};
+
+#[cfg(feature = "actix-web")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
+pub mod builder;
+#[cfg(feature = "reqwest")]
+pub mod client;
+pub mod info;
+pub mod limits;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod pagination;
+pub mod pattern;
+pub mod query;
+pub mod registry;
+#[cfg(feature = "signed")]
+pub mod signed;
+#[cfg(any(feature = "openapi", feature = "typescript"))]
+mod schema;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(feature = "typescript")]
+pub mod typescript;
+pub mod uri;
+
+pub use info::RouteInfo;
+pub use limits::{Limit, ParseLimits};
+pub use uri::Authority;
 
 #[derive(Debug)]
 pub enum RouteParseErr {
@@ -77,6 +115,31 @@ pub enum RouteParseErr {
 	NoQueryString,
 	ParamParseErr(String),
 	QueryParseErr(String),
+	LimitExceeded(Limit),
+	InvalidUrl(String),
+	UnexpectedHost(Option<String>),
+
+	/// The path matched, but the route doesn't accept the request method.
+	/// `allowed` lists the methods which would have matched, for the `Allow`
+	/// header of a 405 response.
+	MethodNotAllowed { allowed: Vec<String> },
+}
+
+impl RouteParseErr {
+	/// The status code of the response to a request which failed to parse:
+	/// `404 Not Found` for paths and hosts which don't match, `405 Method
+	/// Not Allowed` for routes which don't accept the request method, and
+	/// `400 Bad Request` for everything else.
+	#[cfg(feature = "http")]
+	pub fn status_code(&self) -> http::StatusCode {
+		match self {
+			RouteParseErr::NoMatches | RouteParseErr::UnexpectedHost(_) => {
+				http::StatusCode::NOT_FOUND
+			}
+			RouteParseErr::MethodNotAllowed { .. } => http::StatusCode::METHOD_NOT_ALLOWED,
+			_ => http::StatusCode::BAD_REQUEST,
+		}
+	}
 }
 
 impl std::fmt::Display for RouteParseErr {
@@ -89,5 +152,216 @@ pub trait AppRoute: std::fmt::Display + std::str::FromStr {
 	fn path_pattern() -> String
 	where
 		Self: Sized;
+
+	/// Every route declared by this type, one for a struct and one per
+	/// nested route for enum routers and `#[nested]` fields. Enum routers
+	/// list their routes in the order they are tried when parsing.
+	fn routes() -> Vec<RouteInfo>
+	where
+		Self: Sized;
+
+	/// The routes as [RFC 6570](https://tools.ietf.org/html/rfc6570) URI
+	/// templates, e.g. `/groups/{group_id}/users{?limit,offset}`, in the
+	/// order of `routes()`.
+	fn uri_templates() -> Vec<String>
+	where
+		Self: Sized,
+	{
+		Self::routes().iter().map(RouteInfo::uri_template).collect()
+	}
+
 	fn query_string(&self) -> Option<String>;
+
+	/// Parses a route from the request method, the host (without port) and
+	/// the path and query of a URL, checking them against `limits` first.
+	/// Routes with a `host` pattern fail with `UnexpectedHost` if `host`
+	/// doesn't match it, or if `host` is `None` and the pattern has
+	/// parameters. Routes declared with a `method` fail with
+	/// `MethodNotAllowed` if `method` isn't one of them. A `method` of `None`
+	/// matches any route.
+	fn from_parts(
+		method: Option<&str>,
+		host: Option<&str>,
+		path_and_query: &str,
+		limits: &ParseLimits,
+	) -> Result<Self, RouteParseErr>
+	where
+		Self: Sized;
+
+	/// Parses `app_path` like `FromStr`, but first checks it against
+	/// `limits`. Use this for URLs which come from untrusted input.
+	fn from_str_with_limits(app_path: &str, limits: &ParseLimits) -> Result<Self, RouteParseErr>
+	where
+		Self: Sized,
+	{
+		Self::from_parts(None, None, app_path, limits)
+	}
+
+	/// Parses `app_path` like `FromStr`, but only matches routes which
+	/// accept the HTTP `method`, such as `"DELETE"`.
+	fn from_method_and_str(method: &str, app_path: &str) -> Result<Self, RouteParseErr>
+	where
+		Self: Sized,
+	{
+		Self::from_parts(Some(method), None, app_path, &ParseLimits::default())
+	}
+
+	/// Parses an absolute URL such as `https://example.com/users/1?x=1`, a
+	/// scheme-relative URL, or a bare path. The authority is ignored.
+	fn parse_url(url: &str) -> Result<Self, RouteParseErr>
+	where
+		Self: Sized,
+	{
+		Self::parse_url_with(url, &Authority::Any, &ParseLimits::default())
+	}
+
+	/// Parses a URL like `parse_url`, checking its host against `authority`
+	/// and the whole URL against `limits`.
+	fn parse_url_with(
+		url: &str,
+		authority: &Authority,
+		limits: &ParseLimits,
+	) -> Result<Self, RouteParseErr>
+	where
+		Self: Sized,
+	{
+		limits.check(url, None)?;
+
+		let parts = uri::split_url(url)?;
+		authority.check(parts.host)?;
+
+		Self::from_parts(None, parts.host, &parts.path_and_query, limits)
+	}
+
+	/// Parses a route from an `http::Request`, taking the host from its URI
+	/// or, failing that, its `Host` header. Only routes which accept the
+	/// request's method are matched.
+	#[cfg(feature = "http")]
+	fn from_request<B>(request: &http::Request<B>) -> Result<Self, RouteParseErr>
+	where
+		Self: Sized,
+	{
+		uri::from_http_request(request)
+	}
+
+	/// The path portion of the route, without the query string.
+	fn path(&self) -> String;
+
+	/// The host of the route, for routes declared with a `host` pattern such
+	/// as `#[route("/dashboard", host = ":tenant.example.com")]`.
+	fn host(&self) -> Option<String> {
+		None
+	}
+
+	/// The pattern of the route which `self` matches, as listed by
+	/// `routes()`. Enum routers compare it across their variants to pick the
+	/// most specific match.
+	#[doc(hidden)]
+	fn __matched_pattern(&self) -> String
+	where
+		Self: Sized,
+	{
+		Self::routes()
+			.into_iter()
+			.map(|route| route.pattern)
+			.max_by_key(|pattern| registry::Specificity::of(pattern))
+			.unwrap_or_default()
+	}
+
+	/// Renders the route as an absolute URL on top of `base`. The path of
+	/// `base` is kept as a prefix, so `https://example.com/api/` and
+	/// `/users/1` give `https://example.com/api/users/1`. Any query or
+	/// fragment on `base` is replaced, and so is its host if the route has
+	/// one.
+	///
+	/// Panics if the route's host is not a valid host name.
+	#[cfg(feature = "url")]
+	fn to_url(&self, base: &url::Url) -> url::Url {
+		let mut url = base.clone();
+
+		if let Some(host) = self.host() {
+			url.set_host(Some(&host)).expect("route host is a valid host name");
+		}
+
+		url.set_path(&uri::join_path(base.path(), &self.path()));
+		url.set_query(self.query_string().as_deref());
+		url.set_fragment(None);
+		url
+	}
+
+	/// Renders the route as an origin-form `http::Uri` (path and query).
+	/// Characters which aren't allowed in a URI are percent-encoded.
+	#[cfg(feature = "http")]
+	fn to_uri(&self) -> http::Uri {
+		uri::encode_path_and_query(&self.path(), self.query_string().as_deref())
+			.parse()
+			.expect("percent-encoded route is a valid URI")
+	}
+
+	/// Renders the route on top of `base`, keeping the path of `base` as a
+	/// prefix like `to_url`. The result is absolute if `base` is, in which
+	/// case the route's host, if it has one, replaces the host of `base`.
+	///
+	/// Panics if the route's host is not a valid host name.
+	#[cfg(feature = "http")]
+	fn to_absolute_uri(&self, base: &http::Uri) -> http::Uri {
+		let path = uri::join_path(base.path(), &self.path());
+
+		let mut parts = base.clone().into_parts();
+
+		if let (Some(host), Some(authority)) = (self.host(), &parts.authority) {
+			let authority = match authority.port() {
+				Some(port) => format!("{}:{}", host, port),
+				None => host,
+			};
+
+			parts.authority = Some(authority.parse().expect("route host is a valid host name"));
+		}
+
+		parts.path_and_query = Some(
+			uri::encode_path_and_query(&path, self.query_string().as_deref())
+				.parse()
+				.expect("percent-encoded route is a valid URI"),
+		);
+
+		http::Uri::from_parts(parts).expect("parts of a valid URI are consistent")
+	}
+
+	/// A canonical rendering of the route, suitable for cache keys and
+	/// signatures. Query parameters are sorted by key and exact duplicates
+	/// are removed, so two routes that compare equal always produce the
+	/// same string regardless of how they were parsed. `None` query values
+	/// are never rendered, and default-valued query fields are left out when
+	/// marked with `#[query(skip_defaults)]` or
+	/// `#[query(skip_defaults = "canonical")]`. Routes with a host are
+	/// rendered as scheme-relative URLs, e.g. `//acme.example.com/dashboard`,
+	/// so routes differing only by host don't collide.
+	fn canonical_string(&self) -> String;
+
+	/// Renders the canonical string with an `expires` time and an
+	/// HMAC-SHA256 `signature` of both appended to the query, for links
+	/// which must not be tampered with. See the [`signed`] module.
+	#[cfg(feature = "signed")]
+	fn to_signed_string(&self, key: &[u8], expires: std::time::SystemTime) -> String
+	where
+		Self: Sized,
+	{
+		signed::sign(self, key, expires)
+	}
+
+	/// Parses a URL rendered by `to_signed_string`, failing with
+	/// `SignatureErr::BadSignature` if it wasn't signed with `key` or has
+	/// been modified, and with `SignatureErr::Expired` if `now` is past its
+	/// expiry.
+	#[cfg(feature = "signed")]
+	fn from_signed_str(
+		signed_url: &str,
+		key: &[u8],
+		now: std::time::SystemTime,
+	) -> Result<Self, signed::SignatureErr>
+	where
+		Self: Sized,
+	{
+		signed::verify(signed_url, key, now)
+	}
 }
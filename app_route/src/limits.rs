@@ -0,0 +1,115 @@
+//! Limits applied to a URL before it is matched and deserialized, for
+//! routes parsed from untrusted input.
+
+use crate::RouteParseErr;
+
+/// The limit which caused a `RouteParseErr::LimitExceeded`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+	UrlLength,
+	Params,
+	Depth,
+	ArrayIndex,
+}
+
+/// Size and nesting limits checked by `AppRoute::from_str_with_limits`.
+///
+/// The `Default` limits are what `FromStr` uses, and don't restrict
+/// anything.
+///
+/// ```
+/// use app_route::ParseLimits;
+///
+/// let limits = ParseLimits {
+///     max_url_length: Some(2048),
+///     max_params: Some(64),
+///     max_depth: Some(2),
+///     max_array_index: Some(100),
+/// };
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseLimits {
+	/// The maximum length in bytes of the whole URL, path and query included.
+	pub max_url_length: Option<usize>,
+
+	/// The maximum number of `key=value` pairs in the query string.
+	pub max_params: Option<usize>,
+
+	/// The maximum number of bracketed segments in a query key.
+	/// `address[building][name]` has a depth of 2. Without a limit, keys
+	/// nested deeper than five levels are not split any further when the
+	/// query is deserialized.
+	pub max_depth: Option<usize>,
+
+	/// The maximum index used in a query key, such as the `999999` in
+	/// `friend_ids[999999]=1`.
+	pub max_array_index: Option<usize>,
+}
+
+impl ParseLimits {
+	/// Limits for URLs taken from incoming requests, used by the axum,
+	/// actix-web and tower integrations unless they're given others: URLs of
+	/// up to 8 KiB, 100 query parameters, keys nested 5 levels deep and
+	/// array indices up to 1000.
+	pub fn untrusted() -> Self {
+		ParseLimits {
+			max_url_length: Some(8 * 1024),
+			max_params: Some(100),
+			max_depth: Some(5),
+			max_array_index: Some(1000),
+		}
+	}
+
+	/// Checks `url` against the limits. `query` is the query string of
+	/// `url`, without the leading `?`.
+	pub fn check(&self, url: &str, query: Option<&str>) -> Result<(), RouteParseErr> {
+		if let Some(max_url_length) = self.max_url_length {
+			if url.len() > max_url_length {
+				return Err(RouteParseErr::LimitExceeded(Limit::UrlLength));
+			}
+		}
+
+		let query = match query {
+			Some(query) => query,
+			None => return Ok(()),
+		};
+
+		let mut num_params = 0;
+
+		for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+			num_params += 1;
+
+			if let Some(max_params) = self.max_params {
+				if num_params > max_params {
+					return Err(RouteParseErr::LimitExceeded(Limit::Params));
+				}
+			}
+
+			let key = pair.split('=').next().unwrap_or(pair);
+			let mut depth = 0;
+
+			for segment in key.split('[').skip(1) {
+				depth += 1;
+
+				if let Some(max_depth) = self.max_depth {
+					if depth > max_depth {
+						return Err(RouteParseErr::LimitExceeded(Limit::Depth));
+					}
+				}
+
+				let segment = segment.trim_end_matches(']');
+
+				if let Some(max_array_index) = self.max_array_index {
+					if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+						match segment.parse::<usize>() {
+							Ok(index) if index <= max_array_index => {}
+							_ => return Err(RouteParseErr::LimitExceeded(Limit::ArrayIndex)),
+						}
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
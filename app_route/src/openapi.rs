@@ -0,0 +1,224 @@
+//! OpenAPI path generation, enabled with the `openapi` feature.
+//!
+//! Derived routes implement [`OpenApi`], which describes each route as an
+//! [`Operation`]: its path in OpenAPI's `{param}` syntax, its methods, and its
+//! path and query parameters. [`OpenApi::path_items`] assembles those into
+//! the `paths` object of an OpenAPI document.
+//!
+//! Path parameter schemas are inferred from the names of the field types, so
+//! `u64` becomes an `integer` and anything unknown a `string`. Query
+//! parameters are found by walking the `Deserialize` implementation of each
+//! `#[query]` type, which respects `#[serde(rename)]` and friends.
+
+use crate::schema::{self, Schema};
+use serde::de::DeserializeOwned;
+pub use serde_json::{json, Map, Value};
+
+/// A route with a single path, one of the entries of `AppRoute::routes()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operation {
+	/// The path in OpenAPI syntax, e.g. `/users/{user_id}`.
+	pub path: String,
+
+	/// The methods the route accepts. Empty if it accepts any method.
+	pub methods: Vec<String>,
+
+	/// OpenAPI `Parameter` objects for the path and query parameters.
+	pub parameters: Vec<Value>,
+}
+
+impl Operation {
+	/// Mounts the operation under a `#[route_prefix]` or the route of a
+	/// struct with a `#[nested]` field, like `RouteInfo::mount`. The
+	/// parameters of the prefix come first.
+	pub fn mount(mut self, prefix: &str, parameters: &[Value], methods: &[&str]) -> Operation {
+		self.path = format!("{}{}", prefix, self.path);
+		self.parameters.splice(0..0, parameters.iter().cloned());
+
+		if !methods.is_empty() {
+			if self.methods.is_empty() {
+				self.methods = methods.iter().map(|m| m.to_string()).collect();
+			} else {
+				self.methods.retain(|m| methods.contains(&m.as_str()));
+			}
+		}
+
+		self
+	}
+}
+
+/// Implemented by the derive with the `openapi` feature.
+pub trait OpenApi {
+	/// One operation per route, in the order of `AppRoute::routes()`.
+	fn operations() -> Vec<Operation>;
+
+	/// The `paths` object of an OpenAPI document, mapping each path to a
+	/// `PathItem` with one operation per method. Routes which accept any
+	/// method are listed under `get`.
+	fn path_items() -> Map<String, Value> {
+		path_items(Self::operations())
+	}
+}
+
+/// Assembles operations into the `paths` object of an OpenAPI document.
+/// When several operations share a path and method, the first one wins.
+/// OpenAPI requires every operation to list its responses, so each gets a
+/// `default` response to be filled in by hand.
+pub fn path_items(operations: Vec<Operation>) -> Map<String, Value> {
+	let mut paths = Map::new();
+
+	for operation in operations {
+		let item = paths
+			.entry(operation.path.clone())
+			.or_insert_with(|| Value::Object(Map::new()))
+			.as_object_mut()
+			.expect("path items are objects");
+
+		let methods = if operation.methods.is_empty() {
+			vec!["get".to_string()]
+		} else {
+			operation.methods.iter().map(|m| m.to_lowercase()).collect()
+		};
+
+		for method in methods {
+			item.entry(method).or_insert_with(|| {
+				json!({
+					"parameters": operation.parameters,
+					"responses": { "default": { "description": "Default response" } },
+				})
+			});
+		}
+	}
+
+	paths
+}
+
+/// Translates a route pattern such as `/users/:user_id/files/:path*` into
+/// OpenAPI's path syntax, `/users/{user_id}/files/{path}`.
+pub fn openapi_path(pattern: &str) -> String {
+	let mut path = String::with_capacity(pattern.len());
+	let mut name: Option<String> = None;
+
+	for c in pattern.chars() {
+		match name {
+			Some(ref param) if c == '/' || c == '*' => {
+				path.push_str(&format!("{{{}}}", param));
+				name = None;
+
+				if c == '/' {
+					path.push(c);
+				}
+			}
+			Some(ref mut param) => param.push(c),
+			None if c == ':' => name = Some(String::new()),
+			None => path.push(c),
+		}
+	}
+
+	if let Some(param) = name {
+		path.push_str(&format!("{{{}}}", param));
+	}
+
+	path
+}
+
+/// A path `Parameter` object, with a schema inferred from the name of the
+/// field's type.
+pub fn path_parameter(name: &str, type_name: &str) -> Value {
+	let schema = match type_name {
+		"u8" | "u16" | "u32" => Schema::Integer(Some("int32"), true),
+		"i8" | "i16" | "i32" => Schema::Integer(Some("int32"), false),
+		"u64" | "usize" => Schema::Integer(Some("int64"), true),
+		"i64" | "isize" => Schema::Integer(Some("int64"), false),
+		"u128" => Schema::Integer(None, true),
+		"i128" => Schema::Integer(None, false),
+		"f32" => Schema::Number("float"),
+		"f64" => Schema::Number("double"),
+		"bool" => Schema::Boolean,
+		_ => Schema::String,
+	};
+
+	json!({
+		"name": name,
+		"in": "path",
+		"required": true,
+		"schema": schema.to_json(),
+	})
+}
+
+/// The query `Parameter` objects for the fields of `T`, one per top-level
+/// field. Nested structs, maps and sequences use the `deepObject` style,
+/// like the bracketed keys `serde_qs` reads. If `T` is an `Option`, none of
+/// the parameters are required.
+pub fn query_parameters<T: DeserializeOwned>() -> Vec<Value> {
+	let (schema, required) = schema::trace::<T>();
+
+	let fields = match schema {
+		Schema::Object(fields) => fields,
+		_ => return vec![],
+	};
+
+	fields
+		.into_iter()
+		.map(|(name, schema, field_required)| {
+			let mut parameter = json!({
+				"name": name,
+				"in": "query",
+				"required": required && field_required,
+			});
+
+			if let Schema::Object(_) | Schema::Map | Schema::Array(_) = schema {
+				parameter["style"] = json!("deepObject");
+				parameter["explode"] = json!(true);
+			}
+
+			parameter["schema"] = schema.to_json();
+			parameter
+		})
+		.collect()
+}
+
+impl Schema {
+	fn to_json(&self) -> Value {
+		match self {
+			Schema::Unknown => json!({}),
+			Schema::Boolean => json!({ "type": "boolean" }),
+			Schema::Integer(format, unsigned) => {
+				let mut schema = json!({ "type": "integer" });
+
+				if let Some(format) = format {
+					schema["format"] = json!(format);
+				}
+
+				if *unsigned {
+					schema["minimum"] = json!(0);
+				}
+
+				schema
+			}
+			Schema::Number(format) => json!({ "type": "number", "format": format }),
+			Schema::String => json!({ "type": "string" }),
+			Schema::Enum(variants) => json!({ "type": "string", "enum": variants }),
+			Schema::Array(items) => json!({ "type": "array", "items": items.to_json() }),
+			Schema::Map => json!({ "type": "object" }),
+			Schema::Object(fields) => {
+				let properties: Map<String, Value> = fields
+					.iter()
+					.map(|(name, schema, _)| (name.to_string(), schema.to_json()))
+					.collect();
+
+				let required: Vec<_> = fields
+					.iter()
+					.filter(|(_, _, required)| *required)
+					.map(|(name, _, _)| name)
+					.collect();
+
+				if required.is_empty() {
+					json!({ "type": "object", "properties": properties })
+				} else {
+					json!({ "type": "object", "properties": properties, "required": required })
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,174 @@
+//! Links between the pages of list routes.
+//!
+//! Query types implement [`Pagination`] (or [`CursorPagination`]), usually
+//! with `#[derive(Pagination)]`, and routes holding one through
+//! [`QueryMut`] (see `#[app_route(builder)]`) get [`Paginate::next_page`]
+//! and friends:
+//!
+//! ```ignore
+//! #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Pagination)]
+//! #[pagination(limit = "limit", offset = "offset", default_limit = 20)]
+//! struct LimitOffsetQuery {
+//!     limit: Option<u64>,
+//!     offset: Option<u64>,
+//! }
+//!
+//! let next = route.next_page();
+//! let header = link_header(&route, Some(total_count));
+//! ```
+
+use crate::builder::QueryMut;
+use std::fmt;
+
+/// A query paginated with a page size and an offset.
+pub trait Pagination {
+	/// The number of items per page.
+	fn limit(&self) -> u64;
+
+	/// The number of items before the current page.
+	fn offset(&self) -> u64;
+
+	fn set_offset(&mut self, offset: u64);
+}
+
+/// A query paginated with an opaque cursor, which the server hands out with
+/// each page.
+pub trait CursorPagination {
+	/// Sets the cursor, or clears it for the first page.
+	fn set_cursor(&mut self, cursor: Option<&str>);
+}
+
+/// Page navigation for routes with a [`Pagination`] query. Pages are
+/// numbered from 1.
+pub trait Paginate<Q: Pagination>: QueryMut<Q> + Clone {
+	/// The route for page `n`, keeping the page size.
+	fn page(&self, n: u64) -> Self {
+		self.with_query(|query| {
+			let offset = n.saturating_sub(1).saturating_mul(query.limit());
+			query.set_offset(offset);
+		})
+	}
+
+	/// The page the route is on, rounded up if the offset isn't a multiple
+	/// of the page size.
+	fn page_number(&self) -> u64 {
+		let mut route = self.clone();
+		let query = route.query_mut();
+
+		match query.limit() {
+			0 => 1,
+			limit => query.offset().div_ceil(limit) + 1,
+		}
+	}
+
+	fn next_page(&self) -> Self {
+		self.with_query(|query| {
+			let offset = query.offset().saturating_add(query.limit());
+			query.set_offset(offset);
+		})
+	}
+
+	/// The previous page, or `None` on the first page.
+	fn prev_page(&self) -> Option<Self> {
+		let mut route = self.clone();
+		let query = route.query_mut();
+
+		if query.offset() == 0 {
+			return None;
+		}
+
+		let offset = query.offset().saturating_sub(query.limit());
+		query.set_offset(offset);
+		Some(route)
+	}
+}
+
+impl<R: QueryMut<Q> + Clone, Q: Pagination> Paginate<Q> for R {}
+
+/// Page navigation for routes with a [`CursorPagination`] query.
+pub trait PaginateCursor<Q: CursorPagination>: QueryMut<Q> + Clone {
+	/// The route for the page at `cursor`, or the first page.
+	fn cursor_page(&self, cursor: Option<&str>) -> Self {
+		self.with_query(|query| query.set_cursor(cursor))
+	}
+}
+
+impl<R: QueryMut<Q> + Clone, Q: CursorPagination> PaginateCursor<Q> for R {}
+
+/// An [RFC 8288](https://tools.ietf.org/html/rfc8288) `Link` header value,
+/// rendered by `Display`, e.g. `</users?offset=20>; rel="next"`.
+///
+/// Routes render as relative references, which clients resolve against the
+/// request URL.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinkHeader {
+	links: Vec<(String, String)>,
+}
+
+impl LinkHeader {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a link to `target`, which is usually a route or an absolute URL.
+	pub fn link<T: fmt::Display>(mut self, rel: &str, target: &T) -> Self {
+		self.links.push((target.to_string(), rel.to_string()));
+		self
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.links.is_empty()
+	}
+}
+
+impl fmt::Display for LinkHeader {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (i, (target, rel)) in self.links.iter().enumerate() {
+			if i > 0 {
+				write!(f, ", ")?;
+			}
+
+			write!(f, "<{}>; rel=\"{}\"", target, rel)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// The `first`, `prev`, `next` and `last` links for a page of `total`
+/// items. Without a `total`, there is always a `next` link and never a
+/// `last` one.
+pub fn link_header<R, Q>(route: &R, total: Option<u64>) -> LinkHeader
+where
+	R: Paginate<Q> + fmt::Display,
+	Q: Pagination,
+{
+	let mut current = route.clone();
+	let (limit, offset) = {
+		let query = current.query_mut();
+		(query.limit(), query.offset())
+	};
+
+	let mut header = LinkHeader::new().link("first", &route.page(1));
+
+	if let Some(prev) = route.prev_page() {
+		header = header.link("prev", &prev);
+	}
+
+	match total {
+		Some(total) => {
+			if offset.saturating_add(limit) < total {
+				header = header.link("next", &route.next_page());
+			}
+
+			let last_page = if limit == 0 {
+				1
+			} else {
+				total.div_ceil(limit).max(1)
+			};
+
+			header.link("last", &route.page(last_page))
+		}
+		None => header.link("next", &route.next_page()),
+	}
+}
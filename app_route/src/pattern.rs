@@ -0,0 +1,23 @@
+//! Composing the `path_pattern()` regexes of routes which are mounted under
+//! a prefix, used by enum routers and `#[nested]` fields.
+
+use regex::Regex;
+
+/// Joins `prefix` (a regex without a trailing `$`) with the path patterns of
+/// the routes mounted under it. Only the prefix keeps its named captures, as
+/// the nested routes may reuse the same names.
+pub fn nest(prefix: &str, patterns: &[String]) -> String {
+	lazy_static::lazy_static! {
+		static ref CAPTURE_NAME: Regex = Regex::new(r"\(\?P<[^>]+>").expect("Failed to compile regex");
+	}
+
+	let patterns: Vec<_> = patterns
+		.iter()
+		.map(|p| {
+			let p = p.trim_start_matches('^').trim_end_matches('$');
+			CAPTURE_NAME.replace_all(p, "(").into_owned()
+		})
+		.collect();
+
+	format!("{}(?:{})$", prefix, patterns.join("|"))
+}
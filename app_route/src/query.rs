@@ -0,0 +1,290 @@
+//! Query string encoding and decoding used by the generated `FromStr` and
+//! `Display` implementations.
+//!
+//! `serde_qs` does the heavy lifting, but `+` handling is normalized here
+//! before a query is handed to it, so the behaviour doesn't depend on which
+//! `serde_qs` version ends up in the dependency graph.
+
+use crate::{ParseLimits, RouteParseErr};
+use serde::{
+	de::{self, DeserializeOwned, Visitor},
+	forward_to_deserialize_any, Serialize,
+};
+use std::borrow::Cow;
+
+/// How spaces are represented in a query string.
+///
+/// Selected per query field with `#[query(spaces = "plus")]` (the default)
+/// or `#[query(spaces = "percent")]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpaceEncoding {
+	/// `application/x-www-form-urlencoded` style. Spaces are rendered as `+`
+	/// and a bare `+` in a parsed query decodes to a space.
+	#[default]
+	Plus,
+
+	/// RFC 3986 style. Spaces are rendered as `%20` and a bare `+` in a
+	/// parsed query is a literal plus sign.
+	Percent,
+}
+
+/// Rewrites every bare `+` in `query` into its percent-encoded equivalent
+/// for the given space encoding. `%2B` and `%20` are left alone, so the
+/// result decodes the same way no matter how the underlying parser treats `+`.
+pub fn normalize(query: &str, spaces: SpaceEncoding) -> Cow<'_, str> {
+	if !query.contains('+') {
+		return Cow::Borrowed(query);
+	}
+
+	let replacement = match spaces {
+		SpaceEncoding::Plus => "%20",
+		SpaceEncoding::Percent => "%2B",
+	};
+
+	Cow::Owned(query.replace('+', replacement))
+}
+
+/// The nesting depth `serde_qs` uses by default.
+const DEFAULT_MAX_DEPTH: usize = 5;
+
+/// Deserializes a query string (without the leading `?`) into `T`, nesting
+/// query keys up to `limits.max_depth` levels deep.
+pub fn from_str<T: DeserializeOwned>(
+	query: &str,
+	spaces: SpaceEncoding,
+	limits: &ParseLimits,
+) -> Result<T, RouteParseErr> {
+	serde_qs::Config::new(limits.max_depth.unwrap_or(DEFAULT_MAX_DEPTH), true)
+		.deserialize_str(&normalize(query, spaces))
+		.map_err(|e| RouteParseErr::QueryParseErr(e.to_string()))
+}
+
+/// Deserializes the query string of an `Option<T>` query field.
+///
+/// No query string at all gives `None`. If the query contains any top-level
+/// key belonging to `T`, it has to deserialize successfully, and a failure
+/// is reported as `QueryParseErr` rather than being turned into `None`.
+/// Otherwise the query is meant for some other field, and gives `None`.
+pub fn from_str_optional<T: DeserializeOwned>(
+	query: Option<&str>,
+	spaces: SpaceEncoding,
+	limits: &ParseLimits,
+) -> Result<Option<T>, RouteParseErr> {
+	let query = match query {
+		Some(query) => query,
+		None => return Ok(None),
+	};
+
+	let is_relevant = match struct_fields::<T>() {
+		Some(fields) => top_level_keys(query).any(|key| fields.contains(&key.as_ref())),
+		// Not a plain struct (a map, or a struct using `#[serde(flatten)]`),
+		// so any key could belong to it.
+		None => top_level_keys(query).next().is_some(),
+	};
+
+	if is_relevant {
+		from_str(query, spaces, limits).map(Some)
+	} else {
+		Ok(None)
+	}
+}
+
+/// The percent-decoded keys of a query, up to their first `[`.
+fn top_level_keys(query: &str) -> impl Iterator<Item = Cow<'_, str>> {
+	query
+		.split('&')
+		.filter(|pair| !pair.is_empty())
+		.map(|pair| pair.split('=').next().unwrap_or(pair))
+		.map(|key| match percent_decode(key) {
+			Cow::Borrowed(key) => Cow::Borrowed(key.split('[').next().unwrap_or(key)),
+			Cow::Owned(key) => Cow::Owned(key.split('[').next().unwrap_or(&key).to_string()),
+		})
+}
+
+fn percent_decode(input: &str) -> Cow<'_, str> {
+	if !input.contains('%') {
+		return Cow::Borrowed(input);
+	}
+
+	let bytes = input.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+
+	while i < bytes.len() {
+		let hex = bytes
+			.get(i + 1..i + 3)
+			.and_then(|hex| std::str::from_utf8(hex).ok())
+			.and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+		match hex {
+			Some(byte) if bytes[i] == b'%' => {
+				decoded.push(byte);
+				i += 3;
+			}
+			_ => {
+				decoded.push(bytes[i]);
+				i += 1;
+			}
+		}
+	}
+
+	Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// The top-level keys of a `#[query]` field of type `T`, which may be an
+/// `Option`. Empty unless `T` deserializes itself as a plain struct.
+pub fn field_names<T: DeserializeOwned>() -> Vec<&'static str> {
+	struct_fields::<T>().map(<[_]>::to_vec).unwrap_or_default()
+}
+
+/// Returns the field names `T` asks for when it deserializes itself as a
+/// struct (or an `Option` of one), or `None` if it deserializes as anything
+/// else.
+fn struct_fields<T: DeserializeOwned>() -> Option<&'static [&'static str]> {
+	let mut fields = None;
+	let _ = T::deserialize(FieldNameDeserializer(&mut fields));
+	fields
+}
+
+#[derive(Debug)]
+struct FieldNameErr;
+
+impl std::fmt::Display for FieldNameErr {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "field name introspection")
+	}
+}
+
+impl std::error::Error for FieldNameErr {}
+
+impl de::Error for FieldNameErr {
+	fn custom<M: std::fmt::Display>(_msg: M) -> Self {
+		FieldNameErr
+	}
+}
+
+/// A deserializer which records the field names of the struct being
+/// deserialized and then bails out.
+struct FieldNameDeserializer<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de, 'a> de::Deserializer<'de> for FieldNameDeserializer<'a> {
+	type Error = FieldNameErr;
+
+	fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+		Err(FieldNameErr)
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		fields: &'static [&'static str],
+		_visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		*self.0 = Some(fields);
+		Err(FieldNameErr)
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		visitor.visit_some(self)
+	}
+
+	forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct newtype_struct seq tuple
+		tuple_struct map enum identifier ignored_any
+	}
+}
+
+/// Serializes `query` into a query string (without the leading `?`).
+///
+/// Literal `+` characters are always percent-encoded, so the output parses
+/// back into the same value with [`from_str`] and the same `spaces` setting.
+pub fn to_string<T: Serialize>(query: &T, spaces: SpaceEncoding) -> Option<String> {
+	let encoded = serde_qs::to_string(query).ok()?;
+
+	match spaces {
+		SpaceEncoding::Plus => Some(encoded),
+		SpaceEncoding::Percent => Some(encoded.replace('+', "%20")),
+	}
+}
+
+/// Serializes `query` like [`to_string`], but leaves out every top-level
+/// key whose encoding is identical to the one produced by `T::default()`.
+/// Returns `None` if every key was left out.
+pub fn to_string_without_defaults<T: Serialize + Default>(
+	query: &T,
+	spaces: SpaceEncoding,
+) -> Option<String> {
+	let encoded = to_string(query, spaces)?;
+	let defaults = to_string(&T::default(), spaces).unwrap_or_default();
+	let stripped = strip_defaults(&encoded, &defaults);
+
+	if stripped.is_empty() {
+		None
+	} else {
+		Some(stripped)
+	}
+}
+
+/// Removes the top-level keys of `query` which are encoded exactly as they
+/// are in `defaults`. Nested keys such as `address[country]` and indexed
+/// keys such as `ids[0]` are grouped under their top-level key, so a
+/// partially-default value is always kept whole.
+pub fn strip_defaults(query: &str, defaults: &str) -> String {
+	let default_groups = group_by_top_level_key(defaults);
+
+	group_by_top_level_key(query)
+		.into_iter()
+		.filter(|group| !default_groups.contains(group))
+		.flat_map(|(_, pairs)| pairs)
+		.collect::<Vec<_>>()
+		.join("&")
+}
+
+fn group_by_top_level_key(query: &str) -> Vec<(&str, Vec<&str>)> {
+	let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+
+	for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+		let key = pair.split('=').next().unwrap_or(pair);
+		let top_level_key = key.split('[').next().unwrap_or(key);
+
+		match groups.iter_mut().find(|(k, _)| *k == top_level_key) {
+			Some((_, pairs)) => pairs.push(pair),
+			None => groups.push((top_level_key, vec![pair])),
+		}
+	}
+
+	groups
+}
+
+/// Joins the encodings of several `#[query]` fields into a single query
+/// string. Returns `None` if every field was absent or empty.
+pub fn join(encoded_queries: Vec<Option<String>>) -> Option<String> {
+	let filtered: Vec<_> = encoded_queries
+		.into_iter()
+		.flatten()
+		.filter(|query| !query.is_empty())
+		.collect();
+
+	if !filtered.is_empty() {
+		Some(filtered.join("&"))
+	} else {
+		None
+	}
+}
+
+/// Sorts the `key=value` pairs of an encoded query string by key, removing
+/// empty and exactly duplicated pairs. Pairs with the same key keep their
+/// relative order.
+pub fn canonicalize(query: &str) -> String {
+	let mut pairs: Vec<&str> = Vec::new();
+
+	for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+		if !pairs.contains(&pair) {
+			pairs.push(pair);
+		}
+	}
+
+	pairs.sort_by_key(|pair| pair.split('=').next().unwrap_or(pair));
+	pairs.join("&")
+}
@@ -0,0 +1,570 @@
+//! Detecting routes which overlap, so that the order they're tried in
+//! doesn't silently decide which one a URL parses as.
+//!
+//! Enum routers try their variants one at a time, and the first one whose
+//! path matches wins, even if its params then fail to parse. A [`Registry`]
+//! analyses the patterns of a router, or of an explicit list of routes in
+//! the order they're tried, and reports every pair of routes which can match
+//! the same request:
+//!
+//! ```ignore
+//! #[test]
+//! fn routes_dont_conflict() {
+//!     Registry::of::<AppRouter>().validate().unwrap();
+//! }
+//! ```
+//!
+//! A more specific route tried before a more general one, such as
+//! `/users/me` before `/users/:user_id`, is the usual way to special-case a
+//! path and isn't reported.
+//!
+//! [`Registry::suggest`] ranks the routes closest to a path which didn't
+//! match, for "did you mean" hints on 404 pages.
+//!
+//! Enum routers try their variants in order of [`Specificity`], so they
+//! only report conflicts between equally specific routes, or when a
+//! `#[route(priority = N)]` puts a general route first.
+
+use crate::{AppRoute, RouteInfo};
+use lazy_static::lazy_static;
+use std::any::TypeId;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictKind {
+	/// Both routes have the same pattern, up to the names of their params,
+	/// host and methods.
+	Duplicate,
+
+	/// Every request the later route matches is matched by the earlier one
+	/// first, so the later route is unreachable.
+	Shadowed,
+
+	/// Some requests match both routes, but neither is more specific than
+	/// the other, e.g. `/users/:user_id/posts` and `/:org/admin/posts`.
+	Ambiguous,
+}
+
+/// Two routes which can match the same request, in the order they are tried.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+	pub kind: ConflictKind,
+	pub first: RouteInfo,
+	pub second: RouteInfo,
+}
+
+impl fmt::Display for Conflict {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let (first, second) = (&self.first.pattern, &self.second.pattern);
+
+		match self.kind {
+			ConflictKind::Duplicate => write!(f, "`{}` duplicates `{}`", second, first),
+			ConflictKind::Shadowed => write!(f, "`{}` is shadowed by `{}`", second, first),
+			ConflictKind::Ambiguous => write!(f, "`{}` and `{}` are ambiguous", first, second),
+		}
+	}
+}
+
+/// A route which is close to a path that didn't match, from
+/// [`Registry::suggest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+	pub route: RouteInfo,
+
+	/// The number of path segments which have to be inserted, removed or
+	/// replaced for the path to match the route.
+	pub distance: usize,
+
+	/// The index of the first path segment which doesn't match the route,
+	/// e.g. 2 for `/users/1/friend` and `/users/:user_id/friends`. Equal to
+	/// the number of segments if the path stops short of the route.
+	pub diverged_at: usize,
+}
+
+/// A list of routes in the order they are tried when parsing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Registry {
+	routes: Vec<RouteInfo>,
+}
+
+impl Registry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The routes of `R`, e.g. an enum router.
+	pub fn of<R: AppRoute>() -> Self {
+		Self::new().register::<R>()
+	}
+
+	/// Adds the routes of `R` after the ones already registered.
+	pub fn register<R: AppRoute>(mut self) -> Self {
+		self.routes.extend(R::routes());
+		self
+	}
+
+	/// Adds a single route, e.g. one built by hand for a dynamic registry.
+	pub fn register_info(mut self, route: RouteInfo) -> Self {
+		self.routes.push(route);
+		self
+	}
+
+	pub fn routes(&self) -> &[RouteInfo] {
+		&self.routes
+	}
+
+	/// Every pair of conflicting routes, in registration order.
+	pub fn conflicts(&self) -> Vec<Conflict> {
+		let mut conflicts = Vec::new();
+
+		for (i, first) in self.routes.iter().enumerate() {
+			for second in &self.routes[i + 1..] {
+				if let Some(kind) = conflict_kind(first, second) {
+					conflicts.push(Conflict {
+						kind,
+						first: first.clone(),
+						second: second.clone(),
+					});
+				}
+			}
+		}
+
+		conflicts
+	}
+
+	/// The routes closest to `path`, for "did you mean" hints on
+	/// `RouteParseErr::NoMatches`. Routes are ranked by the number of path
+	/// segments which have to be inserted, removed or replaced to match
+	/// them. Routes which don't share a static segment with `path` are left
+	/// out. Query strings, hosts and methods are ignored.
+	pub fn suggest(&self, path: &str) -> Vec<Suggestion> {
+		let path = path.split('?').next().unwrap_or_default();
+		let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+
+		let mut suggestions: Vec<Suggestion> = self
+			.routes
+			.iter()
+			.filter_map(|route| {
+				let pattern = Pattern::path(&route.pattern);
+				let static_segments: Vec<&str> = pattern
+					.0
+					.iter()
+					.filter(|segment| segment.tail == Tail::Exact)
+					.map(|segment| segment.prefix.as_str())
+					.collect();
+
+				if !static_segments.is_empty()
+					&& !static_segments.iter().any(|s| segments.contains(s))
+				{
+					return None;
+				}
+
+				Some(Suggestion {
+					route: route.clone(),
+					distance: edit_distance(&pattern.0, &segments),
+					diverged_at: first_divergence(&pattern.0, &segments),
+				})
+			})
+			.collect();
+
+		// Ties go to routes with as many segments as the path, and then to
+		// the one which matches more of the path's start
+		suggestions.sort_by_key(|s| {
+			let length = Pattern::path(&s.route.pattern).0.len();
+			(
+				s.distance,
+				length.abs_diff(segments.len()),
+				Reverse(s.diverged_at),
+			)
+		});
+		suggestions
+	}
+
+	pub fn validate(&self) -> Result<(), Vec<Conflict>> {
+		let conflicts = self.conflicts();
+
+		if conflicts.is_empty() {
+			Ok(())
+		} else {
+			Err(conflicts)
+		}
+	}
+}
+
+/// How specific a path pattern is. Patterns are compared segment by
+/// segment, where a static segment beats a constrained param such as
+/// `v:version`, which beats a plain `:param`, which beats a `:wildcard*`.
+/// If one pattern runs out of segments first, the longer one is more
+/// specific, unless it continues with a wildcard.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity(Vec<u8>);
+
+impl Specificity {
+	pub fn of(pattern: &str) -> Specificity {
+		let mut ranks: Vec<u8> = Pattern::path(pattern)
+			.0
+			.iter()
+			.map(|segment| match segment.tail {
+				Tail::Exact => 4,
+				Tail::Param if !segment.prefix.is_empty() => 3,
+				Tail::Param => 2,
+				Tail::Wildcard => 0,
+			})
+			.collect();
+
+		// The end of the pattern ranks between a param and a wildcard
+		ranks.push(1);
+
+		Specificity(ranks)
+	}
+}
+
+/// A variant of an enum router, as ranked by [`variant_order`].
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+pub struct RankedVariant {
+	/// The index of the variant, in declaration order.
+	pub index: usize,
+	pub priority: u64,
+	/// The specificity of the variant's most specific route, the best any
+	/// match from the variant can have.
+	pub specificity: Option<Specificity>,
+}
+
+/// The order an enum router tries its variants in, given the priority and
+/// routes of each: highest priority first, then by the specificity of each
+/// variant's most specific route, then in declaration order.
+///
+/// As a variant wrapping another router can have routes of any specificity,
+/// the router keeps trying later variants of the same priority which could
+/// have a more specific match, and picks the most specific one.
+#[doc(hidden)]
+pub fn variant_order(variants: &[(u64, Vec<RouteInfo>)]) -> Vec<RankedVariant> {
+	let mut order: Vec<RankedVariant> = variants
+		.iter()
+		.enumerate()
+		.map(|(index, &(priority, ref routes))| RankedVariant {
+			index,
+			priority,
+			specificity: routes
+				.iter()
+				.map(|route| Specificity::of(&route.pattern))
+				.max(),
+		})
+		.collect();
+
+	order.sort_by(|a, b| {
+		(Reverse(a.priority), Reverse(&a.specificity))
+			.cmp(&(Reverse(b.priority), Reverse(&b.specificity)))
+	});
+
+	order
+}
+
+/// The `variant_order` of the generic router `R`, computed once for each
+/// set of type arguments, as a `static` can't name them.
+#[doc(hidden)]
+pub fn cached_variant_order<R, F>(variants: F) -> &'static [RankedVariant]
+where
+	R: 'static,
+	F: FnOnce() -> Vec<(u64, Vec<RouteInfo>)>,
+{
+	lazy_static! {
+		static ref ORDERS: Mutex<HashMap<TypeId, &'static [RankedVariant]>> =
+			Mutex::new(HashMap::new());
+	}
+
+	let type_id = TypeId::of::<R>();
+
+	if let Some(&order) = ORDERS.lock().unwrap().get(&type_id) {
+		return order;
+	}
+
+	// Computed without holding the lock, as the variants may be generic
+	// routers themselves
+	let order = variant_order(&variants());
+
+	let mut orders = ORDERS.lock().unwrap();
+	orders
+		.entry(type_id)
+		.or_insert_with(|| Box::leak(order.into_boxed_slice()))
+}
+
+/// The routes of an enum router's variants, mounted in the order they are
+/// matched: each step takes the next route of the variant whose next route
+/// has the highest priority and specificity, so every variant's own routes
+/// stay in order.
+#[doc(hidden)]
+pub fn merge_routes(order: &[RankedVariant], mut child_routes: Vec<Vec<RouteInfo>>) -> Vec<RouteInfo> {
+	let mut routes = Vec::new();
+	let mut next: Vec<std::vec::IntoIter<RouteInfo>> = order
+		.iter()
+		.map(|variant| std::mem::take(&mut child_routes[variant.index]).into_iter())
+		.collect();
+
+	loop {
+		let best = next
+			.iter()
+			.zip(order)
+			.enumerate()
+			.filter_map(|(position, (routes, variant))| {
+				let route = routes.as_slice().first()?;
+				Some((position, variant.priority, Specificity::of(&route.pattern)))
+			})
+			.min_by(|a, b| {
+				(Reverse(a.1), Reverse(&a.2), a.0).cmp(&(Reverse(b.1), Reverse(&b.2), b.0))
+			});
+
+		match best {
+			Some((position, _, _)) => routes.extend(next[position].next()),
+			None => return routes,
+		}
+	}
+}
+
+/// Classifies `first` and `second`, where `first` is tried first. Query
+/// strings are ignored, as a route whose path matches is never skipped
+/// because of its query.
+fn conflict_kind(first: &RouteInfo, second: &RouteInfo) -> Option<ConflictKind> {
+	let (a, b) = (
+		Pattern::path(&first.pattern),
+		Pattern::path(&second.pattern),
+	);
+	let (a_host, b_host) = (
+		first.host.as_deref().map(Pattern::host),
+		second.host.as_deref().map(Pattern::host),
+	);
+
+	let overlap = a.overlaps(&b)
+		&& hosts_overlap(&a_host, &b_host)
+		&& methods_overlap(&first.methods, &second.methods);
+
+	if !overlap {
+		return None;
+	}
+
+	let first_contains = a.contains(&b)
+		&& host_contains(&a_host, &b_host)
+		&& methods_contain(&first.methods, &second.methods);
+	let second_contains = b.contains(&a)
+		&& host_contains(&b_host, &a_host)
+		&& methods_contain(&second.methods, &first.methods);
+
+	let same_methods = methods_contain(&first.methods, &second.methods)
+		&& methods_contain(&second.methods, &first.methods);
+
+	if a.contains(&b) && b.contains(&a) && a_host == b_host && same_methods {
+		Some(ConflictKind::Duplicate)
+	} else if first_contains {
+		Some(ConflictKind::Shadowed)
+	} else if second_contains {
+		None
+	} else {
+		Some(ConflictKind::Ambiguous)
+	}
+}
+
+fn hosts_overlap(a: &Option<Pattern>, b: &Option<Pattern>) -> bool {
+	match (a, b) {
+		(Some(a), Some(b)) => a.overlaps(b),
+		_ => true,
+	}
+}
+
+/// Routes without a host match any host.
+fn host_contains(a: &Option<Pattern>, b: &Option<Pattern>) -> bool {
+	match (a, b) {
+		(None, _) => true,
+		(Some(_), None) => false,
+		(Some(a), Some(b)) => a.contains(b),
+	}
+}
+
+/// Routes without methods accept any method.
+fn methods_overlap(a: &[String], b: &[String]) -> bool {
+	a.is_empty() || b.is_empty() || a.iter().any(|m| b.contains(m))
+}
+
+fn methods_contain(a: &[String], b: &[String]) -> bool {
+	a.is_empty() || (!b.is_empty() && b.iter().all(|m| a.contains(m)))
+}
+
+/// What follows the static text of a segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tail {
+	/// Nothing, the segment is static.
+	Exact,
+	/// A `:param`, matching one or more characters up to the next `/`.
+	Param,
+	/// A trailing `:param*`, matching the rest of the path, slashes and all.
+	Wildcard,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Segment {
+	prefix: String,
+	tail: Tail,
+}
+
+/// A path or host pattern split into segments. Params only ever end a
+/// segment, so each one is some static text followed by a [`Tail`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Pattern(Vec<Segment>);
+
+impl Pattern {
+	fn path(pattern: &str) -> Pattern {
+		Pattern::split(pattern.trim_start_matches('/'), '/')
+	}
+
+	fn host(pattern: &str) -> Pattern {
+		Pattern::split(&pattern.to_ascii_lowercase(), '.')
+	}
+
+	fn split(pattern: &str, separator: char) -> Pattern {
+		let segments = pattern
+			.split(separator)
+			.map(|segment| match segment.find(':') {
+				Some(i) if segment.ends_with('*') => Segment {
+					prefix: segment[..i].to_string(),
+					tail: Tail::Wildcard,
+				},
+				Some(i) => Segment {
+					prefix: segment[..i].to_string(),
+					tail: Tail::Param,
+				},
+				None => Segment {
+					prefix: segment.to_string(),
+					tail: Tail::Exact,
+				},
+			})
+			.collect();
+
+		Pattern(segments)
+	}
+
+	/// Whether some path matches both patterns.
+	fn overlaps(&self, other: &Pattern) -> bool {
+		overlaps(&self.0, &other.0)
+	}
+
+	/// Whether every path matching `other` also matches `self`.
+	fn contains(&self, other: &Pattern) -> bool {
+		contains(&self.0, &other.0)
+	}
+}
+
+fn overlaps(a: &[Segment], b: &[Segment]) -> bool {
+	match (a.first(), b.first()) {
+		(None, None) => true,
+		(Some(x), Some(y)) if x.tail == Tail::Wildcard || y.tail == Tail::Wildcard => {
+			let (wildcard, other) = if x.tail == Tail::Wildcard {
+				(x, y)
+			} else {
+				(y, x)
+			};
+
+			match other.tail {
+				Tail::Exact => other.prefix.starts_with(&wildcard.prefix),
+				_ => {
+					other.prefix.starts_with(&wildcard.prefix)
+						|| wildcard.prefix.starts_with(&other.prefix)
+				}
+			}
+		}
+		(Some(x), Some(y)) => segments_overlap(x, y) && overlaps(&a[1..], &b[1..]),
+		_ => false,
+	}
+}
+
+fn segments_overlap(x: &Segment, y: &Segment) -> bool {
+	match (x.tail, y.tail) {
+		(Tail::Exact, Tail::Exact) => x.prefix == y.prefix,
+		(Tail::Exact, _) => x.prefix.starts_with(&y.prefix) && x.prefix.len() > y.prefix.len(),
+		(_, Tail::Exact) => y.prefix.starts_with(&x.prefix) && y.prefix.len() > x.prefix.len(),
+		_ => x.prefix.starts_with(&y.prefix) || y.prefix.starts_with(&x.prefix),
+	}
+}
+
+fn contains(a: &[Segment], b: &[Segment]) -> bool {
+	match (a.first(), b.first()) {
+		(None, None) => true,
+		(Some(x), Some(y)) if x.tail == Tail::Wildcard => y.prefix.starts_with(&x.prefix),
+		(Some(_), Some(y)) if y.tail == Tail::Wildcard => false,
+		(Some(x), Some(y)) => segment_contains(x, y) && contains(&a[1..], &b[1..]),
+		_ => false,
+	}
+}
+
+fn segment_contains(x: &Segment, y: &Segment) -> bool {
+	match (x.tail, y.tail) {
+		(Tail::Exact, Tail::Exact) => x.prefix == y.prefix,
+		(Tail::Exact, _) => false,
+		(_, Tail::Exact) => y.prefix.starts_with(&x.prefix) && y.prefix.len() > x.prefix.len(),
+		_ => y.prefix.starts_with(&x.prefix),
+	}
+}
+
+fn segment_matches(segment: &Segment, value: &str) -> bool {
+	match segment.tail {
+		Tail::Exact => value == segment.prefix,
+		Tail::Param => value.starts_with(&segment.prefix) && value.len() > segment.prefix.len(),
+		Tail::Wildcard => value.starts_with(&segment.prefix),
+	}
+}
+
+/// The Levenshtein distance between the segments of a pattern and a path,
+/// where a trailing wildcard can stand in for any number of path segments.
+fn edit_distance(pattern: &[Segment], path: &[&str]) -> usize {
+	// `distances[i][j]` is the distance between the first `i` segments of
+	// the pattern and the first `j` segments of the path
+	let mut distances = vec![vec![0; path.len() + 1]; pattern.len() + 1];
+
+	for (j, distance) in distances[0].iter_mut().enumerate() {
+		*distance = j;
+	}
+
+	for (i, segment) in pattern.iter().enumerate() {
+		distances[i + 1][0] = i + 1;
+
+		// The distance with a wildcard standing in for the path segments so
+		// far, after its first one
+		let mut swallowed = usize::MAX;
+
+		for j in 0..path.len() {
+			let replace = distances[i][j] + usize::from(!segment_matches(segment, path[j]));
+			let mut distance = replace
+				.min(distances[i][j + 1] + 1)
+				.min(distances[i + 1][j] + 1);
+
+			if segment.tail == Tail::Wildcard {
+				swallowed = swallowed.min(replace);
+				distance = distance.min(swallowed);
+			}
+
+			distances[i + 1][j + 1] = distance;
+		}
+	}
+
+	distances[pattern.len()][path.len()]
+}
+
+fn first_divergence(pattern: &[Segment], path: &[&str]) -> usize {
+	for (j, value) in path.iter().enumerate() {
+		match pattern.get(j) {
+			Some(segment) if segment.tail == Tail::Wildcard => {
+				return if segment_matches(segment, value) {
+					path.len()
+				} else {
+					j
+				};
+			}
+			Some(segment) if segment_matches(segment, value) => {}
+			_ => return j,
+		}
+	}
+
+	path.len()
+}
@@ -0,0 +1,372 @@
+//! Schemas of query types, traced from their `Deserialize` implementations
+//! for the `openapi` and `typescript` generators.
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use std::cell::{Cell, RefCell};
+
+/// The schema of `T`, and whether it is required, i.e. not an `Option`.
+///
+/// Fields which may be left out, such as `#[serde(default)]` ones, can't be
+/// told apart from the schema alone, so `T` is deserialized once more
+/// without each required field, and the ones it still deserializes without
+/// are marked optional.
+pub(crate) fn trace<T: DeserializeOwned>() -> (Schema, bool) {
+	let first = Trace::default();
+	let (_, _, complete) = trace_with::<T>(&first);
+
+	let mut optional = Vec::new();
+
+	if complete {
+		for field in first.required.into_inner() {
+			let without_field = Trace {
+				skip: Some(field),
+				..Trace::default()
+			};
+
+			if trace_with::<T>(&without_field).2 {
+				optional.push(field);
+			}
+		}
+	}
+
+	let (schema, required, _) = trace_with::<T>(&Trace {
+		optional,
+		..Trace::default()
+	});
+
+	(schema, required)
+}
+
+/// Traces `T`, returning its schema, whether it's required, and whether it
+/// deserialized successfully.
+fn trace_with<T: DeserializeOwned>(trace: &Trace) -> (Schema, bool, bool) {
+	let mut schema = Schema::Unknown;
+	let mut required = true;
+
+	let result = T::deserialize(SchemaTracer {
+		schema: &mut schema,
+		required: &mut required,
+		trace,
+	});
+
+	(schema, required, result.is_ok())
+}
+
+/// The state of one pass over a type. Fields are identified by the number
+/// of their struct, in the order the structs are visited, and their name.
+#[derive(Default)]
+struct Trace {
+	/// The number of structs visited so far.
+	structs: Cell<usize>,
+
+	/// The required fields visited so far.
+	required: RefCell<Vec<(usize, &'static str)>>,
+
+	/// A field to leave out.
+	skip: Option<(usize, &'static str)>,
+
+	/// Fields known to be optional.
+	optional: Vec<(usize, &'static str)>,
+
+	/// The names of the structs being traced, outermost first, so that
+	/// self-referential types are traced once rather than forever.
+	nesting: RefCell<Vec<&'static str>>,
+}
+
+/// The schema of a type, as far as its `Deserialize` implementation tells.
+/// Integers have an OpenAPI format, if one fits, and whether they're
+/// unsigned. Object fields are `(name, schema, required)` in declaration
+/// order.
+///
+/// The integer and number formats are only read by the OpenAPI generator.
+#[cfg_attr(not(feature = "openapi"), allow(dead_code))]
+pub(crate) enum Schema {
+	Unknown,
+	Boolean,
+	Integer(Option<&'static str>, bool),
+	Number(&'static str),
+	String,
+	Enum(&'static [&'static str]),
+	Array(Box<Schema>),
+	Map,
+	Object(Vec<(&'static str, Schema, bool)>),
+}
+
+#[derive(Debug)]
+struct TraceErr;
+
+impl std::fmt::Display for TraceErr {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "schema tracing")
+	}
+}
+
+impl std::error::Error for TraceErr {}
+
+impl de::Error for TraceErr {
+	fn custom<M: std::fmt::Display>(_msg: M) -> Self {
+		TraceErr
+	}
+}
+
+/// A deserializer which records the schema of the value being deserialized,
+/// handing the visitor placeholder values. `required` is cleared for
+/// `Option`s.
+struct SchemaTracer<'a> {
+	schema: &'a mut Schema,
+	required: &'a mut bool,
+	trace: &'a Trace,
+}
+
+macro_rules! trace_primitive {
+	($($method:ident => $schema:expr, $visit:ident($value:expr);)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+				*self.schema = $schema;
+				visitor.$visit($value)
+			}
+		)*
+	};
+}
+
+impl<'de, 'a> de::Deserializer<'de> for SchemaTracer<'a> {
+	type Error = TraceErr;
+
+	trace_primitive! {
+		deserialize_bool => Schema::Boolean, visit_bool(false);
+		deserialize_i8 => Schema::Integer(Some("int32"), false), visit_i8(0);
+		deserialize_i16 => Schema::Integer(Some("int32"), false), visit_i16(0);
+		deserialize_i32 => Schema::Integer(Some("int32"), false), visit_i32(0);
+		deserialize_i64 => Schema::Integer(Some("int64"), false), visit_i64(0);
+		deserialize_i128 => Schema::Integer(None, false), visit_i128(0);
+		deserialize_u8 => Schema::Integer(Some("int32"), true), visit_u8(0);
+		deserialize_u16 => Schema::Integer(Some("int32"), true), visit_u16(0);
+		deserialize_u32 => Schema::Integer(Some("int32"), true), visit_u32(0);
+		deserialize_u64 => Schema::Integer(Some("int64"), true), visit_u64(0);
+		deserialize_u128 => Schema::Integer(None, true), visit_u128(0);
+		deserialize_f32 => Schema::Number("float"), visit_f32(0.0);
+		deserialize_f64 => Schema::Number("double"), visit_f64(0.0);
+		deserialize_char => Schema::String, visit_char(' ');
+		deserialize_str => Schema::String, visit_str("");
+		deserialize_string => Schema::String, visit_string(String::new());
+		deserialize_bytes => Schema::String, visit_bytes(&[]);
+		deserialize_byte_buf => Schema::String, visit_byte_buf(Vec::new());
+		deserialize_identifier => Schema::String, visit_str("");
+	}
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		*self.schema = Schema::Unknown;
+		visitor.visit_unit()
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		visitor.visit_unit()
+	}
+
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		*self.schema = Schema::Unknown;
+		visitor.visit_unit()
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		self.deserialize_unit(visitor)
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		*self.required = false;
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		// A newtype nested in itself can't be handed a placeholder.
+		if self.trace.nesting.borrow().contains(&name) {
+			*self.schema = Schema::Unknown;
+			return Err(TraceErr);
+		}
+
+		self.trace.nesting.borrow_mut().push(name);
+		let trace = self.trace;
+		let result = visitor.visit_newtype_struct(self);
+		trace.nesting.borrow_mut().pop();
+		result
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		self.deserialize_tuple(1, visitor)
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(
+		self,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		let mut items = Schema::Unknown;
+
+		let result = visitor.visit_seq(ItemsTracer {
+			items: &mut items,
+			remaining: len,
+			trace: self.trace,
+		});
+
+		*self.schema = Schema::Array(Box::new(items));
+		result
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		self.deserialize_tuple(len, visitor)
+	}
+
+	fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		*self.schema = Schema::Map;
+		visitor.visit_map(de::value::MapDeserializer::new(
+			std::iter::empty::<((), ())>(),
+		))
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		// A struct nested in itself is left opaque, and handed no fields so
+		// that its `Option`s end the recursion.
+		if self.trace.nesting.borrow().contains(&name) {
+			*self.schema = Schema::Unknown;
+			return visitor.visit_map(de::value::MapDeserializer::new(
+				std::iter::empty::<((), ())>(),
+			));
+		}
+
+		let mut traced = Vec::new();
+		let id = self.trace.structs.get();
+		self.trace.structs.set(id + 1);
+
+		self.trace.nesting.borrow_mut().push(name);
+		let result = visitor.visit_map(FieldsTracer {
+			id,
+			fields,
+			index: 0,
+			traced: &mut traced,
+			trace: self.trace,
+		});
+		self.trace.nesting.borrow_mut().pop();
+
+		*self.schema = Schema::Object(traced);
+		result
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		*self.schema = Schema::Enum(variants);
+
+		match variants.first() {
+			Some(&variant) => visitor.visit_enum(variant.into_deserializer()),
+			None => Err(TraceErr),
+		}
+	}
+}
+
+/// Hands out `remaining` placeholder items, recording their schema.
+struct ItemsTracer<'a> {
+	items: &'a mut Schema,
+	remaining: usize,
+	trace: &'a Trace,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for ItemsTracer<'a> {
+	type Error = TraceErr;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, Self::Error> {
+		if self.remaining == 0 {
+			return Ok(None);
+		}
+
+		self.remaining -= 1;
+
+		seed.deserialize(SchemaTracer {
+			schema: self.items,
+			required: &mut true,
+			trace: self.trace,
+		})
+		.map(Some)
+	}
+}
+
+/// Hands out every field of a struct but the skipped one, recording their
+/// schemas.
+struct FieldsTracer<'a> {
+	id: usize,
+	fields: &'static [&'static str],
+	index: usize,
+	traced: &'a mut Vec<(&'static str, Schema, bool)>,
+	trace: &'a Trace,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for FieldsTracer<'a> {
+	type Error = TraceErr;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(
+		&mut self,
+		seed: K,
+	) -> Result<Option<K::Value>, Self::Error> {
+		if let Some(&field) = self.fields.get(self.index) {
+			if self.trace.skip == Some((self.id, field)) {
+				self.index += 1;
+			}
+		}
+
+		match self.fields.get(self.index) {
+			Some(&field) => seed.deserialize(field.into_deserializer()).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(
+		&mut self,
+		seed: V,
+	) -> Result<V::Value, Self::Error> {
+		let field = self.fields[self.index];
+		self.index += 1;
+
+		let mut schema = Schema::Unknown;
+		let mut required = true;
+
+		let result = seed.deserialize(SchemaTracer {
+			schema: &mut schema,
+			required: &mut required,
+			trace: self.trace,
+		});
+
+		if required {
+			if self.trace.optional.contains(&(self.id, field)) {
+				required = false;
+			} else {
+				self.trace.required.borrow_mut().push((self.id, field));
+			}
+		}
+
+		self.traced.push((field, schema, required));
+		result
+	}
+}
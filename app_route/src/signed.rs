@@ -0,0 +1,144 @@
+//! Tamper-proof, expiring URLs for download and unsubscribe links, signed
+//! with HMAC-SHA256.
+//!
+//! A signed URL is the route's `canonical_string()` with an `expires` (unix
+//! seconds) and a hex `signature` query parameter appended:
+//!
+//! ```text
+//! /downloads/12?expires=1700000000&signature=5f0e...
+//! ```
+//!
+//! Routes with a host pattern are rendered scheme-relative, with their host:
+//!
+//! ```text
+//! //acme.example.com/dashboard/billing?expires=1700000000&signature=9a41...
+//! ```
+//!
+//! The signature covers the canonical form of the route, host included, and
+//! the expiry, so reordering or re-encoding the query parameters doesn't
+//! invalidate it, but changing any of their values, or the host, does. Both
+//! parameters are stripped before the route is parsed, so they never reach
+//! the typed `#[query]` fields. A route shouldn't have query fields named
+//! `expires` or `signature`.
+//!
+//! Signed URLs come from untrusted clients, so they're parsed with
+//! [`ParseLimits::untrusted`].
+
+use crate::{AppRoute, Authority, ParseLimits, RouteParseErr};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug)]
+pub enum SignatureErr {
+	/// The signature or expiry is missing, malformed, or doesn't match the
+	/// route. The URL was not signed with this key, or has been modified.
+	BadSignature,
+
+	/// The signature is valid, but the URL expired at the given time.
+	Expired(SystemTime),
+
+	/// The URL doesn't parse as the route, once the signature is removed.
+	Route(RouteParseErr),
+}
+
+impl std::fmt::Display for SignatureErr {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{:?}", self)
+	}
+}
+
+impl From<RouteParseErr> for SignatureErr {
+	fn from(err: RouteParseErr) -> Self {
+		SignatureErr::Route(err)
+	}
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or(0)
+}
+
+fn mac(key: &[u8], canonical: &str, expires: u64) -> HmacSha256 {
+	let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+	mac.update(canonical.as_bytes());
+	mac.update(b"\n");
+	mac.update(expires.to_string().as_bytes());
+	mac
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// `usize::is_multiple_of` needs Rust 1.87
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+	if hex.len() % 2 != 0 || !hex.is_ascii() {
+		return None;
+	}
+
+	(0..hex.len())
+		.step_by(2)
+		.map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+		.collect()
+}
+
+/// Splits `name=value` off the end of a query, returning the URL before it
+/// with its `?` kept, but not its `&`.
+fn split_last_param<'a>(url: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+	let (rest, value) = url.rsplit_once(&format!("{}=", name))?;
+
+	match rest.strip_suffix('&') {
+		Some(rest) => Some((rest, value)),
+		None if rest.ends_with('?') => Some((rest, value)),
+		None => None,
+	}
+}
+
+/// Renders `route` as a signed URL which expires at `expires`. See
+/// [`AppRoute::to_signed_string`].
+pub fn sign<R: AppRoute>(route: &R, key: &[u8], expires: SystemTime) -> String {
+	let canonical = route.canonical_string();
+	let expires = unix_seconds(expires);
+	let signature = to_hex(&mac(key, &canonical, expires).finalize().into_bytes());
+
+	let separator = if canonical.contains('?') { "&" } else { "?" };
+
+	format!(
+		"{}{}expires={}&signature={}",
+		canonical, separator, expires, signature
+	)
+}
+
+/// Parses and verifies a signed URL. See [`AppRoute::from_signed_str`].
+pub fn verify<R: AppRoute>(
+	signed_url: &str,
+	key: &[u8],
+	now: SystemTime,
+) -> Result<R, SignatureErr> {
+	let (unsigned, signature) =
+		split_last_param(signed_url, "signature").ok_or(SignatureErr::BadSignature)?;
+	let (app_path, expires) =
+		split_last_param(unsigned, "expires").ok_or(SignatureErr::BadSignature)?;
+
+	let signature = from_hex(signature).ok_or(SignatureErr::BadSignature)?;
+	let expires: u64 = expires.parse().map_err(|_| SignatureErr::BadSignature)?;
+
+	let route = R::parse_url_with(app_path, &Authority::Any, &ParseLimits::untrusted())?;
+
+	mac(key, &route.canonical_string(), expires)
+		.verify_slice(&signature)
+		.map_err(|_| SignatureErr::BadSignature)?;
+
+	let expires = UNIX_EPOCH + Duration::from_secs(expires);
+
+	if now >= expires {
+		return Err(SignatureErr::Expired(expires));
+	}
+
+	Ok(route)
+}
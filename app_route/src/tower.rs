@@ -0,0 +1,322 @@
+//! [tower](https://docs.rs/tower) services which use a route type as the
+//! router, enabled with the `tower` feature.
+//!
+//! [`RouteService`] parses each request into a route (usually an enum
+//! router) and hands both to a handler, so it can sit directly on top of
+//! hyper. [`RouteLayer`] parses the route in front of another service and
+//! stores it in the request's extensions.
+//!
+//! ```ignore
+//! use app_route::tower::RouteService;
+//!
+//! let service = RouteService::new(|route: AppRouter, request: Request<Incoming>| async move {
+//!     Ok::<_, Infallible>(match route {
+//!         AppRouter::User(user) => show_user(user, request).await,
+//!         AppRouter::Login(login) => login_page(login).await,
+//!     })
+//! });
+//! ```
+
+use crate::{uri, AppRoute, ParseLimits, RouteParseErr};
+use http::{header, HeaderValue, Request, Response};
+use pin_project_lite::pin_project;
+use std::{
+	future::Future,
+	marker::PhantomData,
+	pin::Pin,
+	task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The default response to a request which failed to parse, with an empty
+/// body and `RouteParseErr::status_code()`. `405 Method Not Allowed`
+/// responses list the allowed methods in their `Allow` header.
+pub fn error_response<B: Default>(error: RouteParseErr) -> Response<B> {
+	let mut response = Response::new(B::default());
+	*response.status_mut() = error.status_code();
+
+	if let RouteParseErr::MethodNotAllowed { ref allowed } = error {
+		if let Ok(allow) = HeaderValue::from_str(&allowed.join(", ")) {
+			response.headers_mut().insert(header::ALLOW, allow);
+		}
+	}
+
+	response
+}
+
+/// Produces the response to a request which failed to parse. Implemented
+/// for closures taking a `RouteParseErr`.
+pub trait ErrorHandler<B> {
+	fn handle(&mut self, error: RouteParseErr) -> Response<B>;
+}
+
+impl<F, B> ErrorHandler<B> for F
+where
+	F: FnMut(RouteParseErr) -> Response<B>,
+{
+	fn handle(&mut self, error: RouteParseErr) -> Response<B> {
+		self(error)
+	}
+}
+
+/// The default [`ErrorHandler`], which responds with [`error_response`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultErrorHandler;
+
+impl<B: Default> ErrorHandler<B> for DefaultErrorHandler {
+	fn handle(&mut self, error: RouteParseErr) -> Response<B> {
+		error_response(error)
+	}
+}
+
+/// A `Service` which parses requests into `R` and calls `handler` with the
+/// route and the request.
+///
+/// Requests which don't parse are answered by the [`ErrorHandler`], which
+/// defaults to [`error_response`] and can be replaced with
+/// [`RouteService::on_error`]. Requests are checked against
+/// `ParseLimits::untrusted()` unless [`RouteService::limits`] is used.
+pub struct RouteService<R, H, E> {
+	handler: H,
+	on_error: E,
+	limits: ParseLimits,
+	_route: PhantomData<fn() -> R>,
+}
+
+impl<R, H> RouteService<R, H, DefaultErrorHandler> {
+	/// Creates a service calling `handler`, which returns a future resolving
+	/// to a `Result<Response<_>, _>` like a `tower::service_fn` would.
+	pub fn new(handler: H) -> Self {
+		RouteService {
+			handler,
+			on_error: DefaultErrorHandler,
+			limits: ParseLimits::untrusted(),
+			_route: PhantomData,
+		}
+	}
+}
+
+impl<R, H, E> RouteService<R, H, E> {
+	/// Replaces the handler for requests which don't parse into `R`.
+	pub fn on_error<E2>(self, on_error: E2) -> RouteService<R, H, E2> {
+		RouteService {
+			handler: self.handler,
+			on_error,
+			limits: self.limits,
+			_route: PhantomData,
+		}
+	}
+
+	/// Replaces the limits requests are checked against.
+	pub fn limits(mut self, limits: ParseLimits) -> Self {
+		self.limits = limits;
+		self
+	}
+}
+
+impl<R, H: Clone, E: Clone> Clone for RouteService<R, H, E> {
+	fn clone(&self) -> Self {
+		RouteService {
+			handler: self.handler.clone(),
+			on_error: self.on_error.clone(),
+			limits: self.limits.clone(),
+			_route: PhantomData,
+		}
+	}
+}
+
+impl<R, H, E, F, ReqBody, ResBody, Err> Service<Request<ReqBody>> for RouteService<R, H, E>
+where
+	R: AppRoute,
+	H: FnMut(R, Request<ReqBody>) -> F,
+	F: Future<Output = Result<Response<ResBody>, Err>>,
+	E: ErrorHandler<ResBody>,
+{
+	type Response = Response<ResBody>;
+	type Error = Err;
+	type Future = RouteFuture<F, ResBody>;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+		match uri::from_http(
+			request.method(),
+			request.uri(),
+			request.headers(),
+			&self.limits,
+		) {
+			Ok(route) => RouteFuture::inner((self.handler)(route, request)),
+			Err(error) => RouteFuture::error(self.on_error.handle(error)),
+		}
+	}
+}
+
+/// A `Layer` which parses requests into `R` before they reach the inner
+/// service, and inserts the route into the request's extensions.
+///
+/// Requests which don't parse never reach the inner service, and are
+/// answered by the error handler like in [`RouteService`], and are checked
+/// against `ParseLimits::untrusted()` unless [`RouteLayer::limits`] is used.
+pub struct RouteLayer<R, E> {
+	on_error: E,
+	limits: ParseLimits,
+	_route: PhantomData<fn() -> R>,
+}
+
+impl<R> RouteLayer<R, DefaultErrorHandler> {
+	/// Creates a layer responding to requests which don't parse with
+	/// [`error_response`].
+	pub fn new() -> Self {
+		RouteLayer {
+			on_error: DefaultErrorHandler,
+			limits: ParseLimits::untrusted(),
+			_route: PhantomData,
+		}
+	}
+}
+
+impl<R> Default for RouteLayer<R, DefaultErrorHandler> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<R, E> RouteLayer<R, E> {
+	/// Replaces the handler for requests which don't parse into `R`.
+	pub fn on_error<E2>(self, on_error: E2) -> RouteLayer<R, E2> {
+		RouteLayer {
+			on_error,
+			limits: self.limits,
+			_route: PhantomData,
+		}
+	}
+
+	/// Replaces the limits requests are checked against.
+	pub fn limits(mut self, limits: ParseLimits) -> Self {
+		self.limits = limits;
+		self
+	}
+}
+
+impl<R, E: Clone> Clone for RouteLayer<R, E> {
+	fn clone(&self) -> Self {
+		RouteLayer {
+			on_error: self.on_error.clone(),
+			limits: self.limits.clone(),
+			_route: PhantomData,
+		}
+	}
+}
+
+impl<R, E: Clone, S> Layer<S> for RouteLayer<R, E> {
+	type Service = RouteMiddleware<R, S, E>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		RouteMiddleware {
+			inner,
+			on_error: self.on_error.clone(),
+			limits: self.limits.clone(),
+			_route: PhantomData,
+		}
+	}
+}
+
+/// The service produced by [`RouteLayer`].
+pub struct RouteMiddleware<R, S, E> {
+	inner: S,
+	on_error: E,
+	limits: ParseLimits,
+	_route: PhantomData<fn() -> R>,
+}
+
+impl<R, S: Clone, E: Clone> Clone for RouteMiddleware<R, S, E> {
+	fn clone(&self) -> Self {
+		RouteMiddleware {
+			inner: self.inner.clone(),
+			on_error: self.on_error.clone(),
+			limits: self.limits.clone(),
+			_route: PhantomData,
+		}
+	}
+}
+
+impl<R, S, E, ReqBody, ResBody> Service<Request<ReqBody>> for RouteMiddleware<R, S, E>
+where
+	R: AppRoute + Clone + Send + Sync + 'static,
+	S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+	E: ErrorHandler<ResBody>,
+{
+	type Response = Response<ResBody>;
+	type Error = S::Error;
+	type Future = RouteFuture<S::Future, ResBody>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+		match uri::from_http::<R>(
+			request.method(),
+			request.uri(),
+			request.headers(),
+			&self.limits,
+		) {
+			Ok(route) => {
+				request.extensions_mut().insert(route);
+				RouteFuture::inner(self.inner.call(request))
+			}
+			Err(error) => RouteFuture::error(self.on_error.handle(error)),
+		}
+	}
+}
+
+pin_project! {
+	/// The future returned by [`RouteService`] and [`RouteMiddleware`].
+	pub struct RouteFuture<F, B> {
+		#[pin]
+		state: RouteFutureState<F, B>,
+	}
+}
+
+pin_project! {
+	#[project = RouteFutureStateProj]
+	enum RouteFutureState<F, B> {
+		Inner { #[pin] future: F },
+		Error { response: Option<Response<B>> },
+	}
+}
+
+impl<F, B> RouteFuture<F, B> {
+	fn inner(future: F) -> Self {
+		RouteFuture {
+			state: RouteFutureState::Inner { future },
+		}
+	}
+
+	fn error(response: Response<B>) -> Self {
+		RouteFuture {
+			state: RouteFutureState::Error {
+				response: Some(response),
+			},
+		}
+	}
+}
+
+impl<F, B, Err> Future for RouteFuture<F, B>
+where
+	F: Future<Output = Result<Response<B>, Err>>,
+{
+	type Output = Result<Response<B>, Err>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		match self.project().state.project() {
+			RouteFutureStateProj::Inner { future } => future.poll(cx),
+			RouteFutureStateProj::Error { response } => Poll::Ready(Ok(response
+				.take()
+				.expect("RouteFuture polled after completion"))),
+		}
+	}
+}
@@ -0,0 +1,517 @@
+//! TypeScript route helper generation, enabled with the `typescript` feature.
+//!
+//! Derived routes implement [`TypeScript`], which describes each route as a
+//! [`Helper`]. [`generate`] turns those into a TypeScript module with one
+//! function per route, returning the same string as the route's `Display`:
+//!
+//! ```ignore
+//! // build.rs of a crate which depends on the crate defining the routes
+//! fn main() {
+//!     app_route::typescript::write("web/src/routes.ts", &AppRouter::helpers()).unwrap();
+//! }
+//! ```
+//!
+//! ```typescript
+//! import { usersList } from "./routes";
+//!
+//! usersList({ group_id: 1, query: { limit: 20 } }); // "/groups/1/users?limit=20"
+//! ```
+//!
+//! Query objects are serialized like `serde_qs` does, with bracketed keys
+//! such as `filters[tags][0]=a`, in the order of the Rust struct's fields.
+
+use crate::query::SpaceEncoding;
+use crate::schema::{self, Schema};
+use serde::de::DeserializeOwned;
+use std::{fs, io, path::Path};
+
+/// A path or prefix param of a route.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+	pub name: String,
+
+	/// The TypeScript type, e.g. `number`.
+	pub ts_type: String,
+}
+
+impl Param {
+	/// A param with a TypeScript type inferred from the name of the field's
+	/// type. Unknown types are `string`s.
+	pub fn new(name: &str, type_name: &str) -> Param {
+		let ts_type = match type_name {
+			"u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+			| "i128" | "isize" | "f32" | "f64" => "number",
+			"bool" => "boolean",
+			_ => "string",
+		};
+
+		Param {
+			name: name.to_string(),
+			ts_type: ts_type.to_string(),
+		}
+	}
+}
+
+/// A `#[query]` field of a route.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryField {
+	pub name: String,
+
+	/// The TypeScript type of the query object.
+	pub ts_type: String,
+
+	/// Whether the field is an `Option`.
+	pub optional: bool,
+
+	/// The order and nesting of the query's keys, as a TypeScript `Shape`
+	/// literal understood by the generated `encodeQuery`.
+	pub shape: String,
+
+	pub spaces: SpaceEncoding,
+
+	/// The encoding of the query type's `Default`, for fields with
+	/// `#[query(skip_defaults)]`.
+	pub defaults: Option<String>,
+}
+
+impl QueryField {
+	/// Describes a query field of type `T`, which may be an `Option`.
+	pub fn new<T: DeserializeOwned>(
+		name: &str,
+		spaces: SpaceEncoding,
+		defaults: Option<String>,
+	) -> QueryField {
+		let (schema, required) = schema::trace::<T>();
+
+		QueryField {
+			name: name.to_string(),
+			ts_type: ts_type(&schema),
+			optional: !required,
+			shape: shape(&schema),
+			spaces,
+			defaults,
+		}
+	}
+}
+
+/// A route with a single path, one of the entries of `AppRoute::routes()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Helper {
+	/// The name of the route type, e.g. `UsersListRoute`.
+	pub name: String,
+
+	/// The names of the routers and nested routes this route is mounted
+	/// under, outermost first. Used to tell apart routes with the same name.
+	pub scope: Vec<String>,
+
+	/// The route pattern, e.g. `/groups/:group_id/users`.
+	pub pattern: String,
+
+	pub params: Vec<Param>,
+	pub queries: Vec<QueryField>,
+}
+
+impl Helper {
+	/// Mounts the helper under a `#[route_prefix]` or the route of a struct
+	/// with a `#[nested]` field, like `RouteInfo::mount`. The params and
+	/// queries of the parent come first.
+	pub fn mount(
+		mut self,
+		scope: &str,
+		prefix: &str,
+		params: &[Param],
+		queries: &[QueryField],
+	) -> Helper {
+		self.scope.insert(0, scope.to_string());
+		self.pattern = format!("{}{}", prefix, self.pattern);
+		self.params.splice(0..0, params.iter().cloned());
+		self.queries.splice(0..0, queries.iter().cloned());
+		self
+	}
+}
+
+/// Implemented by the derive with the `typescript` feature.
+pub trait TypeScript {
+	/// One helper per route, in the order of `AppRoute::routes()`.
+	fn helpers() -> Vec<Helper>;
+
+	/// The TypeScript module for all of the routes, see [`generate`].
+	fn typescript() -> String {
+		generate(&Self::helpers())
+	}
+}
+
+/// Generates a TypeScript module exporting a function per route, named
+/// after the route type without its `Route` suffix (`UsersListRoute`
+/// becomes `usersList`), along with an interface for its params.
+///
+/// Routes which end up with the same name are prefixed with the names of
+/// the routers they're mounted under.
+///
+/// # Panics
+///
+/// If two routes still end up with the same name, or a route has two
+/// params with the same name.
+pub fn generate(helpers: &[Helper]) -> String {
+	let mut output = String::from(RUNTIME);
+	let mut names: Vec<String> = Vec::new();
+
+	for helper in helpers {
+		let name = function_name(&helper.name);
+		let duplicated = helpers
+			.iter()
+			.filter(|h| function_name(&h.name) == name)
+			.count() > 1;
+
+		let name = if duplicated {
+			let mut qualified: String = helper.scope.iter().map(|s| strip_route(s)).collect();
+			qualified.push_str(strip_route(&helper.name));
+			lower_first(&qualified)
+		} else {
+			name
+		};
+
+		if names.contains(&name) {
+			panic!("Two routes generate the TypeScript helper `{}`", name);
+		}
+
+		output.push('\n');
+		output.push_str(&helper_function(&name, helper));
+		names.push(name);
+	}
+
+	output
+}
+
+/// Writes the output of [`generate`] to `path`, for use in build scripts.
+/// The file is left untouched when its contents haven't changed, so tools
+/// watching it don't rebuild needlessly.
+pub fn write<P: AsRef<Path>>(path: P, helpers: &[Helper]) -> io::Result<()> {
+	let path = path.as_ref();
+	let output = generate(helpers);
+
+	match fs::read_to_string(path) {
+		Ok(ref existing) if *existing == output => Ok(()),
+		_ => fs::write(path, output),
+	}
+}
+
+fn helper_function(name: &str, helper: &Helper) -> String {
+	let mut field_names: Vec<&str> = Vec::new();
+	let mut fields = String::new();
+
+	for param in &helper.params {
+		field_names.push(&param.name);
+		fields.push_str(&format!(
+			"\t{}: {};\n",
+			property(&param.name),
+			param.ts_type
+		));
+	}
+
+	for query in &helper.queries {
+		field_names.push(&query.name);
+
+		if query.optional {
+			fields.push_str(&format!(
+				"\t{}?: {} | null;\n",
+				property(&query.name),
+				query.ts_type
+			));
+		} else {
+			fields.push_str(&format!(
+				"\t{}: {};\n",
+				property(&query.name),
+				query.ts_type
+			));
+		}
+	}
+
+	for (i, field_name) in field_names.iter().enumerate() {
+		if field_names[..i].contains(field_name) {
+			panic!(
+				"Route `{}` has two params named `{}` in its TypeScript helper",
+				helper.name, field_name
+			);
+		}
+	}
+
+	let path = template(&helper.pattern);
+
+	if field_names.is_empty() {
+		return format!(
+			"export function {}(): string {{\n\treturn {};\n}}\n",
+			name, path
+		);
+	}
+
+	let interface = format!("{}Params", upper_first(name));
+
+	let body = if helper.queries.is_empty() {
+		format!("\treturn {};\n", path)
+	} else {
+		let queries: Vec<String> = helper.queries.iter().map(encode_query).collect();
+		format!(
+			"\treturn withQuery({}, [\n{}\t]);\n",
+			path,
+			queries
+				.iter()
+				.map(|q| format!("\t\t{},\n", q))
+				.collect::<String>()
+		)
+	};
+
+	format!(
+		"export interface {interface} {{\n{fields}}}\n\nexport function {name}(params: {interface}): string {{\n{body}}}\n",
+		interface = interface,
+		fields = fields,
+		name = name,
+		body = body,
+	)
+}
+
+/// The expression encoding a query field, or giving `null` when it's absent.
+fn encode_query(query: &QueryField) -> String {
+	let spaces = match query.spaces {
+		SpaceEncoding::Plus => "plus",
+		SpaceEncoding::Percent => "percent",
+	};
+
+	let value = format!("params[{}]", string_literal(&query.name));
+	let mut encoded = format!("encodeQuery({}, {}, \"{}\")", value, query.shape, spaces);
+
+	if let Some(ref defaults) = query.defaults {
+		encoded = format!("stripDefaults({}, {})", encoded, string_literal(defaults));
+	}
+
+	if query.optional {
+		format!("{} == null ? null : {}", value, encoded)
+	} else {
+		encoded
+	}
+}
+
+/// A template literal rendering a route pattern, e.g.
+/// `` `/users/${params.user_id}` ``.
+fn template(pattern: &str) -> String {
+	let mut template = String::from("`");
+	let mut name: Option<String> = None;
+
+	for c in pattern.chars() {
+		match name {
+			Some(ref param) if c == '/' || c == '*' => {
+				template.push_str(&format!("${{params[{}]}}", string_literal(param)));
+				name = None;
+
+				if c == '/' {
+					template.push(c);
+				}
+			}
+			Some(ref mut param) => param.push(c),
+			None if c == ':' => name = Some(String::new()),
+			None if c == '`' || c == '$' || c == '\\' => {
+				template.push('\\');
+				template.push(c);
+			}
+			None => template.push(c),
+		}
+	}
+
+	if let Some(param) = name {
+		template.push_str(&format!("${{params[{}]}}", string_literal(&param)));
+	}
+
+	template.push('`');
+	template
+}
+
+fn ts_type(schema: &Schema) -> String {
+	match schema {
+		Schema::Unknown => "unknown".to_string(),
+		Schema::Boolean => "boolean".to_string(),
+		Schema::Integer(..) | Schema::Number(_) => "number".to_string(),
+		Schema::String => "string".to_string(),
+		Schema::Enum(variants) => variants
+			.iter()
+			.map(|v| string_literal(v))
+			.collect::<Vec<_>>()
+			.join(" | "),
+		Schema::Array(items) => format!("Array<{}>", ts_type(items)),
+		Schema::Map => "Record<string, unknown>".to_string(),
+		Schema::Object(fields) => {
+			let fields: Vec<String> = fields
+				.iter()
+				.map(|(name, schema, required)| {
+					if *required {
+						format!("{}: {}", property(name), ts_type(schema))
+					} else {
+						format!("{}?: {} | null", property(name), ts_type(schema))
+					}
+				})
+				.collect();
+
+			format!("{{ {} }}", fields.join("; "))
+		}
+	}
+}
+
+fn shape(schema: &Schema) -> String {
+	match schema {
+		Schema::Array(items) => format!("{{ items: {} }}", shape(items)),
+		Schema::Map => "\"map\"".to_string(),
+		Schema::Object(fields) => {
+			let fields: Vec<String> = fields
+				.iter()
+				.map(|(name, schema, _)| format!("[{}, {}]", string_literal(name), shape(schema)))
+				.collect();
+
+			format!("{{ fields: [{}] }}", fields.join(", "))
+		}
+		_ => "null".to_string(),
+	}
+}
+
+/// A property name, quoted unless it's a valid identifier.
+fn property(name: &str) -> String {
+	let is_identifier = name
+		.chars()
+		.next()
+		.map(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+		.unwrap_or(false)
+		&& name
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+
+	if is_identifier {
+		name.to_string()
+	} else {
+		string_literal(name)
+	}
+}
+
+fn string_literal(value: &str) -> String {
+	let mut literal = String::from("\"");
+
+	for c in value.chars() {
+		match c {
+			'"' => literal.push_str("\\\""),
+			'\\' => literal.push_str("\\\\"),
+			'\n' => literal.push_str("\\n"),
+			'\r' => literal.push_str("\\r"),
+			c if (c as u32) < 0x20 => literal.push_str(&format!("\\u{:04x}", c as u32)),
+			c => literal.push(c),
+		}
+	}
+
+	literal.push('"');
+	literal
+}
+
+fn strip_route(name: &str) -> &str {
+	match name.strip_suffix("Route") {
+		Some(stripped) if !stripped.is_empty() => stripped,
+		_ => name,
+	}
+}
+
+fn function_name(type_name: &str) -> String {
+	lower_first(strip_route(type_name))
+}
+
+fn lower_first(name: &str) -> String {
+	let mut chars = name.chars();
+
+	match chars.next() {
+		Some(first) => first.to_lowercase().chain(chars).collect(),
+		None => String::new(),
+	}
+}
+
+fn upper_first(name: &str) -> String {
+	let mut chars = name.chars();
+
+	match chars.next() {
+		Some(first) => first.to_uppercase().chain(chars).collect(),
+		None => String::new(),
+	}
+}
+
+/// Shared by every generated module: a port of `serde_qs` serialization and
+/// of `app_route::query::{join, strip_defaults}`.
+const RUNTIME: &str = r#"// Generated by app_route. Do not edit.
+
+type Shape = null | "map" | { items: Shape } | { fields: [string, Shape][] };
+
+function encodeComponent(text: string): string {
+	return encodeURIComponent(text)
+		.replace(/[!'()~]/g, (c) => "%" + c.charCodeAt(0).toString(16).toUpperCase())
+		.replace(/%20/g, "+");
+}
+
+function encodeValue(pairs: string[], key: string, value: unknown, shape: Shape): void {
+	if (value === null || value === undefined) {
+		return;
+	}
+
+	const child = (name: string) => {
+		const encoded = encodeComponent(name.replace(/ /g, "+"));
+		return key === "" ? encoded : `${key}[${encoded}]`;
+	};
+
+	if (Array.isArray(value)) {
+		const items = shape !== null && typeof shape === "object" && "items" in shape ? shape.items : null;
+		value.forEach((item, i) => encodeValue(pairs, child(String(i)), item, items));
+	} else if (typeof value === "object") {
+		const object = value as Record<string, unknown>;
+		const fields: [string, Shape][] =
+			shape !== null && typeof shape === "object" && "fields" in shape
+				? shape.fields
+				: Object.keys(object).map((name) => [name, null]);
+
+		for (const [name, fieldShape] of fields) {
+			encodeValue(pairs, child(name), object[name], fieldShape);
+		}
+	} else {
+		pairs.push(`${key}=${encodeComponent(String(value))}`);
+	}
+}
+
+function encodeQuery(query: unknown, shape: Shape, spaces: "plus" | "percent"): string {
+	const pairs: string[] = [];
+	encodeValue(pairs, "", query, shape);
+
+	const encoded = pairs.join("&");
+	return spaces === "percent" ? encoded.replace(/\+/g, "%20") : encoded;
+}
+
+function groupByTopLevelKey(query: string): [string, string[]][] {
+	const groups: [string, string[]][] = [];
+
+	for (const pair of query.split("&").filter((pair) => pair !== "")) {
+		const key = pair.split("=")[0].split("[")[0];
+		const group = groups.find(([k]) => k === key);
+
+		if (group) {
+			group[1].push(pair);
+		} else {
+			groups.push([key, [pair]]);
+		}
+	}
+
+	return groups;
+}
+
+function stripDefaults(query: string, defaults: string): string {
+	const defaultGroups = groupByTopLevelKey(defaults).map(([, pairs]) => pairs.join("&"));
+
+	return groupByTopLevelKey(query)
+		.map(([, pairs]) => pairs.join("&"))
+		.filter((group) => !defaultGroups.includes(group))
+		.join("&");
+}
+
+function withQuery(path: string, queries: (string | null)[]): string {
+	const present = queries.filter((query): query is string => query !== null && query !== "");
+	return present.length > 0 ? `${path}?${present.join("&")}` : path;
+}
+"#;
@@ -0,0 +1,265 @@
+//! Parsing routes out of absolute URLs such as
+//! `https://example.com/users/1?x=1`, as well as `http::Uri` and `url::Url`
+//! when the `http` and `url` features are enabled.
+
+use crate::RouteParseErr;
+#[cfg(any(feature = "http", feature = "url"))]
+use crate::{AppRoute, ParseLimits};
+use std::borrow::Cow;
+
+/// How the authority (`user@host:port`) of an absolute URL is treated when
+/// a route is parsed out of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Authority {
+	/// Accept any host, or none at all.
+	Any,
+
+	/// Require one of these hosts. Ports and user info are ignored, hosts
+	/// are compared case-insensitively, and a URL without a host is rejected.
+	Hosts(Vec<String>),
+}
+
+impl Authority {
+	/// Checks the host of a URL, as returned by [`split_url`].
+	pub fn check(&self, host: Option<&str>) -> Result<(), RouteParseErr> {
+		match self {
+			Authority::Any => Ok(()),
+			Authority::Hosts(hosts) => match host {
+				Some(host) if hosts.iter().any(|h| h.eq_ignore_ascii_case(host)) => Ok(()),
+				_ => Err(RouteParseErr::UnexpectedHost(host.map(str::to_string))),
+			},
+		}
+	}
+}
+
+/// A URL split into the parts a route cares about.
+#[derive(Debug, PartialEq)]
+pub struct UrlParts<'a> {
+	/// The host, without user info or port. `None` for a bare path.
+	pub host: Option<&'a str>,
+
+	/// The path and query string, always starting with `/`. The fragment
+	/// is dropped, as it is never sent to a server.
+	pub path_and_query: Cow<'a, str>,
+}
+
+/// Splits an absolute URL (`https://example.com/users?x=1`), a
+/// scheme-relative URL (`//example.com/users`) or a bare path (`/users`)
+/// into its host and its path and query.
+pub fn split_url(url: &str) -> Result<UrlParts<'_>, RouteParseErr> {
+	let url = match url.find('#') {
+		Some(hash_pos) => &url[..hash_pos],
+		None => url,
+	};
+
+	let after_scheme = if let Some(after_slashes) = url.strip_prefix("//") {
+		after_slashes
+	} else if url.starts_with('/') {
+		return Ok(UrlParts {
+			host: None,
+			path_and_query: Cow::Borrowed(url),
+		});
+	} else {
+		let scheme_end = url
+			.find("://")
+			.filter(|&end| is_scheme(&url[..end]))
+			.ok_or_else(|| RouteParseErr::InvalidUrl(url.to_string()))?;
+
+		&url[(scheme_end + 3)..]
+	};
+
+	let authority_end = after_scheme.find(['/', '?']).unwrap_or(after_scheme.len());
+	let (authority, path_and_query) = after_scheme.split_at(authority_end);
+
+	let host = host_from_authority(authority);
+
+	if host.is_empty() {
+		return Err(RouteParseErr::InvalidUrl(url.to_string()));
+	}
+
+	let path_and_query = if path_and_query.starts_with('/') {
+		Cow::Borrowed(path_and_query)
+	} else {
+		Cow::Owned(format!("/{}", path_and_query))
+	};
+
+	Ok(UrlParts {
+		host: Some(host),
+		path_and_query,
+	})
+}
+
+fn is_scheme(scheme: &str) -> bool {
+	let mut chars = scheme.chars();
+
+	match chars.next() {
+		Some(c) if c.is_ascii_alphabetic() => {
+			chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+		}
+		_ => false,
+	}
+}
+
+pub(crate) fn host_from_authority(authority: &str) -> &str {
+	let host_and_port = match authority.rfind('@') {
+		Some(at_pos) => &authority[(at_pos + 1)..],
+		None => authority,
+	};
+
+	if host_and_port.starts_with('[') {
+		// IPv6 literal, keep the brackets
+		match host_and_port.find(']') {
+			Some(end) => &host_and_port[..=end],
+			None => host_and_port,
+		}
+	} else {
+		match host_and_port.find(':') {
+			Some(colon_pos) => &host_and_port[..colon_pos],
+			None => host_and_port,
+		}
+	}
+}
+
+/// Mounts the canonical string of a child route under a router's prefix,
+/// keeping the `//host` in front if the child has one.
+#[doc(hidden)]
+pub fn mount_canonical(prefix: &str, host: Option<String>, canonical: String) -> String {
+	match host {
+		Some(host) => {
+			let path_and_query = &canonical[(2 + host.len())..];
+			format!("//{}{}{}", host, prefix, path_and_query)
+		}
+		None => format!("{}{}", prefix, canonical),
+	}
+}
+
+/// Appends a route's path onto the path of a base URL, so a base of
+/// `/api/` or `/api` and a route of `/users` give `/api/users`.
+#[cfg(any(feature = "http", feature = "url"))]
+pub(crate) fn join_path(base_path: &str, route_path: &str) -> String {
+	format!("{}{}", base_path.trim_end_matches('/'), route_path)
+}
+
+/// Percent-encodes the characters of a rendered route which aren't allowed
+/// in a URI, such as spaces and non-ASCII characters in path parameters, and
+/// joins the path and query. A `#` is always encoded, and so is a `?` in the
+/// path, so parameters containing them can't cut the path short.
+#[cfg(feature = "http")]
+pub(crate) fn encode_path_and_query(path: &str, query: Option<&str>) -> String {
+	let mut encoded = encode_uri_chars(path, b"#?").into_owned();
+
+	if let Some(query) = query {
+		encoded.push('?');
+		encoded.push_str(&encode_uri_chars(query, b"#"));
+	}
+
+	encoded
+}
+
+#[cfg(feature = "http")]
+fn encode_uri_chars<'a>(part: &'a str, reserved: &[u8]) -> Cow<'a, str> {
+	let is_allowed = |byte: u8| {
+		byte.is_ascii_graphic()
+			&& !reserved.contains(&byte)
+			&& !matches!(
+				byte,
+				b'"' | b'<' | b'>' | b'\\' | b'^' | b'`' | b'{' | b'|' | b'}'
+			)
+	};
+
+	if part.bytes().all(is_allowed) {
+		return Cow::Borrowed(part);
+	}
+
+	let mut encoded = String::with_capacity(part.len());
+
+	for byte in part.bytes() {
+		if is_allowed(byte) {
+			encoded.push(byte as char);
+		} else {
+			encoded.push_str(&format!("%{:02X}", byte));
+		}
+	}
+
+	Cow::Owned(encoded)
+}
+
+/// Parses a route from an `http::Uri`.
+#[cfg(feature = "http")]
+pub fn from_http_uri<R: AppRoute>(uri: &http::Uri) -> Result<R, RouteParseErr> {
+	let path_and_query = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+	R::from_parts(None, uri.host(), path_and_query, &ParseLimits::default())
+}
+
+/// Parses a route from an `http::Request`. The host is taken from the
+/// request's URI, or from its `Host` header for origin-form URIs.
+#[cfg(feature = "http")]
+pub fn from_http_request<R: AppRoute, B>(request: &http::Request<B>) -> Result<R, RouteParseErr> {
+	from_http(
+		request.method(),
+		request.uri(),
+		request.headers(),
+		&ParseLimits::default(),
+	)
+}
+
+/// Parses a route from the `http::request::Parts` of a request, like
+/// [`from_http_request`].
+#[cfg(feature = "http")]
+pub fn from_http_parts<R: AppRoute>(parts: &http::request::Parts) -> Result<R, RouteParseErr> {
+	from_http(
+		&parts.method,
+		&parts.uri,
+		&parts.headers,
+		&ParseLimits::default(),
+	)
+}
+
+#[cfg(feature = "http")]
+pub(crate) fn from_http<R: AppRoute>(
+	method: &http::Method,
+	uri: &http::Uri,
+	headers: &http::HeaderMap,
+	limits: &ParseLimits,
+) -> Result<R, RouteParseErr> {
+	let host_header = headers
+		.get(http::header::HOST)
+		.and_then(|host| host.to_str().ok());
+
+	from_request_parts(
+		method.as_str(),
+		uri.host(),
+		host_header,
+		uri.path_and_query().map(|p| p.as_str()),
+		limits,
+	)
+}
+
+/// Parses a route from the pieces of a request, for the framework
+/// integrations which use their own `http` types. The host is taken from
+/// the URI, falling back to the `Host` header.
+#[cfg(feature = "http")]
+pub(crate) fn from_request_parts<R: AppRoute>(
+	method: &str,
+	uri_host: Option<&str>,
+	host_header: Option<&str>,
+	path_and_query: Option<&str>,
+	limits: &ParseLimits,
+) -> Result<R, RouteParseErr> {
+	let host = uri_host.or_else(|| host_header.map(host_from_authority));
+
+	R::from_parts(Some(method), host, path_and_query.unwrap_or("/"), limits)
+}
+
+/// Parses a route from a `url::Url`.
+#[cfg(feature = "url")]
+pub fn from_url<R: AppRoute>(url: &::url::Url) -> Result<R, RouteParseErr> {
+	use ::url::Position;
+
+	R::from_parts(
+		None,
+		url.host_str(),
+		&url[Position::BeforePath..Position::AfterQuery],
+		&ParseLimits::default(),
+	)
+}
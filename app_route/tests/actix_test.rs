@@ -0,0 +1,165 @@
+#![cfg(feature = "actix-web")]
+
+use actix_web::test::{call_and_read_body, call_service, init_service, TestRequest};
+use actix_web::{http::header, http::StatusCode, web, App};
+use app_route::actix::{actix_path, resource};
+use app_route::{AppRoute, ParseLimits};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct PageQuery {
+	page: u32,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id")]
+struct UserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users")]
+struct UserListRoute {
+	#[query]
+	query: PageQuery,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", method = "DELETE")]
+struct DeleteUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/files/:path*")]
+struct FileRoute {
+	path: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route_prefix("/api")]
+enum ApiRoute {
+	User(UserRoute),
+	DeleteUser(DeleteUserRoute),
+	File(FileRoute),
+}
+
+async fn show_user(route: UserRoute) -> String {
+	format!("user {}", route.user_id)
+}
+
+async fn list_users(route: UserListRoute) -> String {
+	format!("page {}", route.query.page)
+}
+
+async fn api(route: ApiRoute) -> String {
+	route.to_string()
+}
+
+#[test]
+fn translates_patterns() {
+	assert_eq!(actix_path("/users/:user_id"), "/users/{user_id}");
+	assert_eq!(
+		actix_path("/api/:org_id/files/:path*"),
+		"/api/{org_id}/files/{path:.*}"
+	);
+	assert_eq!(actix_path("/users:tail*"), "/users{tail:.*}");
+	assert_eq!(actix_path("/users"), "/users");
+}
+
+#[test]
+fn escapes_literal_braces() {
+	let pattern = actix_path("/files/{raw}/:id");
+	assert_eq!(
+		pattern,
+		"/files/{app_route_brace0:\\x7B}raw{app_route_brace1:\\x7D}/{id}"
+	);
+
+	let resource = actix_web::dev::ResourceDef::new(pattern);
+	assert!(resource.is_match("/files/{raw}/42"));
+	assert!(!resource.is_match("/files/raw/42"));
+}
+
+#[actix_web::test]
+async fn extracts_routes() {
+	let app = init_service(
+		App::new()
+			.service(resource::<UserRoute>().route(web::get().to(show_user)))
+			.service(resource::<UserListRoute>().route(web::get().to(list_users))),
+	)
+	.await;
+
+	let request = TestRequest::get().uri("/users/5").to_request();
+	let body = call_and_read_body(&app, request).await;
+	assert_eq!(body, "user 5");
+
+	let request = TestRequest::get().uri("/users?page=2").to_request();
+	let body = call_and_read_body(&app, request).await;
+	assert_eq!(body, "page 2");
+
+	let request = TestRequest::get().uri("/users/abc").to_request();
+	let response = call_service(&app, request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn parse_limits() {
+	let app = init_service(
+		App::new().service(resource::<UserListRoute>().route(web::get().to(list_users))),
+	)
+	.await;
+
+	// Untrusted limits apply by default
+	let uri = format!("/users?page=1{}", "&x=1".repeat(100));
+	let request = TestRequest::get().uri(&uri).to_request();
+	let response = call_service(&app, request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	let app = init_service(
+		App::new()
+			.app_data(ParseLimits {
+				max_url_length: Some(13),
+				..ParseLimits::default()
+			})
+			.service(resource::<UserListRoute>().route(web::get().to(list_users))),
+	)
+	.await;
+
+	let request = TestRequest::get().uri("/users?page=2").to_request();
+	let body = call_and_read_body(&app, request).await;
+	assert_eq!(body, "page 2");
+
+	let request = TestRequest::get().uri("/users?page=10").to_request();
+	let response = call_service(&app, request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn registers_enum_routers() {
+	let app = init_service(App::new().service(resource::<ApiRoute>().to(api))).await;
+
+	let request = TestRequest::delete().uri("/api/users/5").to_request();
+	let body = call_and_read_body(&app, request).await;
+	assert_eq!(body, "/api/users/5");
+
+	let request = TestRequest::get().uri("/api/files/a/b.txt").to_request();
+	let body = call_and_read_body(&app, request).await;
+	assert_eq!(body, "/api/files/a/b.txt");
+
+	let request = TestRequest::get().uri("/users/5").to_request();
+	let response = call_service(&app, request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn method_not_allowed() {
+	let app = init_service(
+		App::new().service(resource::<DeleteUserRoute>().to(|_: DeleteUserRoute| async { "" })),
+	)
+	.await;
+
+	let request = TestRequest::get().uri("/users/5").to_request();
+	let response = call_service(&app, request).await;
+	assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+	assert_eq!(response.headers().get(header::ALLOW).unwrap(), "DELETE");
+}
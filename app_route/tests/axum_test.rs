@@ -0,0 +1,198 @@
+#![cfg(feature = "axum")]
+
+use app_route::axum::{axum_path, RouterExt};
+use app_route::http::{header, Request, StatusCode};
+use app_route::{AppRoute, ParseLimits};
+use axum::{body::Body, routing::any, routing::get, Extension, Router};
+use serde::{Deserialize, Serialize};
+use tower::ServiceExt;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct PageQuery {
+	page: u32,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id")]
+struct UserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users")]
+struct UserListRoute {
+	#[query]
+	query: PageQuery,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", method = "DELETE")]
+struct DeleteUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/files/:path*")]
+struct FileRoute {
+	path: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route_prefix("/api")]
+enum ApiRoute {
+	User(UserRoute),
+	DeleteUser(DeleteUserRoute),
+	UserList(UserListRoute),
+	File(FileRoute),
+}
+
+async fn show_user(route: UserRoute) -> String {
+	format!("user {}", route.user_id)
+}
+
+async fn list_users(route: UserListRoute) -> String {
+	format!("page {}", route.query.page)
+}
+
+async fn api(route: ApiRoute) -> String {
+	route.to_string()
+}
+
+async fn send(app: Router, method: &str, uri: &str) -> (StatusCode, String) {
+	let request = Request::builder()
+		.method(method)
+		.uri(uri)
+		.body(Body::empty())
+		.unwrap();
+
+	let response = app.oneshot(request).await.unwrap();
+	let status = response.status();
+	let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+		.await
+		.unwrap();
+
+	(status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[test]
+fn translates_patterns() {
+	assert_eq!(axum_path("/users/:user_id"), "/users/{user_id}");
+	assert_eq!(
+		axum_path("/api/:org_id/files/:path*"),
+		"/api/{org_id}/files/{*path}"
+	);
+	assert_eq!(axum_path("/users"), "/users");
+}
+
+#[test]
+#[should_panic(expected = "doesn't start a segment (`users:tail*`)")]
+fn rejects_mid_segment_wildcards() {
+	axum_path("/users:tail*");
+}
+
+#[test]
+#[should_panic(expected = "doesn't start a segment (`v:version`)")]
+fn rejects_constrained_params() {
+	axum_path("/users/v:version");
+}
+
+#[tokio::test]
+async fn extracts_routes() {
+	let app = Router::new()
+		.app_route::<UserRoute>(get(show_user))
+		.app_route::<UserListRoute>(get(list_users));
+
+	assert_eq!(
+		send(app.clone(), "GET", "/users/5").await,
+		(StatusCode::OK, "user 5".to_string())
+	);
+
+	assert_eq!(
+		send(app.clone(), "GET", "/users?page=2").await,
+		(StatusCode::OK, "page 2".to_string())
+	);
+
+	// Not a valid user_id for the route
+	let (status, _) = send(app.clone(), "GET", "/users/abc").await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	let (status, _) = send(app, "GET", "/users?page=abc").await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn parse_limits() {
+	let app = Router::new().app_route::<UserListRoute>(get(list_users));
+
+	// Untrusted limits apply by default
+	let uri = format!("/users?page=1{}", "&x=1".repeat(100));
+	let (status, _) = send(app.clone(), "GET", &uri).await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	let app = app.layer(Extension(ParseLimits {
+		max_url_length: Some(13),
+		..ParseLimits::default()
+	}));
+
+	assert_eq!(
+		send(app.clone(), "GET", "/users?page=2").await,
+		(StatusCode::OK, "page 2".to_string())
+	);
+
+	let (status, _) = send(app, "GET", "/users?page=10").await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn registers_enum_routers() {
+	let app = Router::new().app_route::<ApiRoute>(any(api));
+
+	assert_eq!(
+		send(app.clone(), "DELETE", "/api/users/5").await,
+		(StatusCode::OK, "/api/users/5".to_string())
+	);
+
+	assert_eq!(
+		send(app.clone(), "GET", "/api/files/a/b.txt").await,
+		(StatusCode::OK, "/api/files/a/b.txt".to_string())
+	);
+
+	let (status, _) = send(app.clone(), "GET", "/users/5").await;
+	assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn method_not_allowed() {
+	let app = Router::new().app_route::<DeleteUserRoute>(any(|_: DeleteUserRoute| async {}));
+
+	let request = Request::builder()
+		.method("GET")
+		.uri("/users/5")
+		.body(Body::empty())
+		.unwrap();
+
+	let response = app.oneshot(request).await.unwrap();
+	assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+	assert_eq!(response.headers()[header::ALLOW], "DELETE");
+}
+
+#[test]
+fn route_info() {
+	let patterns: Vec<_> = ApiRoute::routes()
+		.into_iter()
+		.map(|route| (route.pattern, route.methods))
+		.collect();
+
+	assert_eq!(
+		patterns,
+		vec![
+			("/api/users/:user_id".to_string(), vec![]),
+			(
+				"/api/users/:user_id".to_string(),
+				vec!["DELETE".to_string()]
+			),
+			("/api/users".to_string(), vec![]),
+			("/api/files/:path*".to_string(), vec![]),
+		]
+	);
+}
@@ -0,0 +1,106 @@
+use app_route::builder::QueryMut;
+use app_route::{AppRoute, QueryBuilder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize, QueryBuilder)]
+struct UserListQuery {
+	limit: Option<u64>,
+	offset: Option<u64>,
+	keyword: Option<String>,
+
+	#[serde(default)]
+	friends_only: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize, QueryBuilder)]
+struct TokenQuery {
+	token: Option<String>,
+}
+
+#[derive(AppRoute, Clone, Debug, PartialEq)]
+#[route("/groups/:group_id/users")]
+#[app_route(builder)]
+struct UsersListRoute {
+	group_id: u64,
+
+	#[query]
+	query: UserListQuery,
+}
+
+#[derive(AppRoute, Clone, Debug, PartialEq)]
+#[route("/orgs/:org_id/groups/:group_id")]
+#[app_route(builder)]
+struct GroupRoute {
+	org_id: String,
+
+	#[query]
+	auth: Option<TokenQuery>,
+
+	group_id: u64,
+}
+
+#[test]
+fn builds_with_defaults() {
+	let route = UsersListRoute::builder(4313145).build();
+
+	assert_eq!(
+		route,
+		UsersListRoute {
+			group_id: 4313145,
+			query: UserListQuery::default(),
+		}
+	);
+}
+
+#[test]
+fn query_setters() {
+	let route = UsersListRoute::builder(1)
+		.limit(20)
+		.keyword("some_keyword")
+		.friends_only(true)
+		.build();
+
+	assert_eq!(
+		route.to_string(),
+		"/groups/1/users?limit=20&keyword=some_keyword&friends_only=true"
+	);
+
+	let route = UsersListRoute::builder(1)
+		.query(|q| q.offset = Some(10))
+		.build();
+	assert_eq!(route.query.offset, Some(10));
+
+	let query = UserListQuery::default().limit(5);
+	assert_eq!(query.limit, Some(5));
+}
+
+#[test]
+fn option_queries() {
+	let route = GroupRoute::builder("acme".to_string(), 2).build();
+	assert_eq!(route.auth, None);
+	assert_eq!(route.to_string(), "/orgs/acme/groups/2");
+
+	let route = GroupRoute::builder("acme".to_string(), 2)
+		.token("secret")
+		.build();
+	assert_eq!(route.to_string(), "/orgs/acme/groups/2?token=secret");
+}
+
+#[test]
+fn with_query() {
+	let route = UsersListRoute::builder(1).limit(20).build();
+	let next = route.with_query(|q| q.offset = Some(20));
+
+	assert_eq!(
+		route.to_string(),
+		"/groups/1/users?limit=20&friends_only=false"
+	);
+	assert_eq!(
+		next.to_string(),
+		"/groups/1/users?limit=20&offset=20&friends_only=false"
+	);
+
+	let route = GroupRoute::builder("acme".to_string(), 2).build();
+	let signed = route.with_query(|q: &mut TokenQuery| q.token = Some("secret".to_string()));
+	assert_eq!(signed.to_string(), "/orgs/acme/groups/2?token=secret");
+}
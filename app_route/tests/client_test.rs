@@ -0,0 +1,175 @@
+#![cfg(feature = "reqwest")]
+
+use app_route::client::{Client, ClientError};
+use app_route::AppRoute;
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, body::Incoming, server::conn::http1, service::service_fn};
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use tokio::net::TcpListener;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct NewUser {
+	name: String,
+}
+
+/// What the stand-in server received.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Echo {
+	method: String,
+	uri: String,
+	content_type: Option<String>,
+	body: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users", method = "POST", request = "NewUser", response = "Echo")]
+struct CreateUserRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", response = "Echo")]
+struct ShowUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", method = "DELETE")]
+struct DeleteUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", method = "PUT", request = "NewUser")]
+struct ReplaceUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/missing", response = "Echo")]
+struct MissingRoute {}
+
+async fn handle(request: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+	let method = request.method().to_string();
+	let uri = request.uri().to_string();
+	let content_type = request
+		.headers()
+		.get("content-type")
+		.map(|value| value.to_str().unwrap().to_string());
+	let body = request.into_body().collect().await.unwrap().to_bytes();
+
+	let response = if uri.ends_with("/missing") {
+		Response::builder()
+			.status(StatusCode::NOT_FOUND)
+			.body(Full::new(Bytes::from("not here")))
+	} else if method == "DELETE" {
+		Response::builder()
+			.status(StatusCode::NO_CONTENT)
+			.body(Full::new(Bytes::new()))
+	} else {
+		let echo = Echo {
+			method,
+			uri,
+			content_type,
+			body: String::from_utf8(body.to_vec()).unwrap(),
+		};
+
+		Response::builder().body(Full::new(Bytes::from(serde_json::to_vec(&echo).unwrap())))
+	};
+
+	Ok(response.unwrap())
+}
+
+/// Starts the stand-in server, returning a client for it.
+async fn start_server() -> Client {
+	let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let address = listener.local_addr().unwrap();
+
+	tokio::spawn(async move {
+		loop {
+			let (stream, _) = listener.accept().await.unwrap();
+
+			tokio::spawn(async move {
+				http1::Builder::new()
+					.serve_connection(TokioIo::new(stream), service_fn(handle))
+					.await
+					.unwrap();
+			});
+		}
+	});
+
+	Client::new(format!("http://{}/api/", address).parse().unwrap())
+}
+
+#[tokio::test]
+async fn sends_json_bodies() {
+	let client = start_server().await;
+
+	let new_user = NewUser {
+		name: "Slurmp".to_string(),
+	};
+
+	let echo = client.send(&CreateUserRoute {}, &new_user).await.unwrap();
+	assert_eq!(
+		echo,
+		Echo {
+			method: "POST".to_string(),
+			uri: "/api/users".to_string(),
+			content_type: Some("application/json".to_string()),
+			body: r#"{"name":"Slurmp"}"#.to_string(),
+		}
+	);
+}
+
+#[tokio::test]
+async fn sends_without_bodies() {
+	let client = start_server().await;
+
+	let echo = client
+		.send(&ShowUserRoute { user_id: 5 }, &())
+		.await
+		.unwrap();
+	assert_eq!(
+		echo,
+		Echo {
+			method: "GET".to_string(),
+			uri: "/api/users/5".to_string(),
+			content_type: None,
+			body: "".to_string(),
+		}
+	);
+
+	client
+		.send(&DeleteUserRoute { user_id: 5 }, &())
+		.await
+		.unwrap();
+}
+
+#[tokio::test]
+async fn ignores_unit_response_bodies() {
+	let client = start_server().await;
+
+	let new_user = NewUser {
+		name: "Slurmp".to_string(),
+	};
+
+	// The server responds with a JSON echo, which a `()` response ignores
+	client
+		.send(&ReplaceUserRoute { user_id: 5 }, &new_user)
+		.await
+		.unwrap();
+}
+
+#[tokio::test]
+async fn error_statuses() {
+	let client = start_server().await;
+
+	match client.send(&MissingRoute {}, &()).await {
+		Err(ClientError::Status(status, body)) => {
+			assert_eq!(status, StatusCode::NOT_FOUND);
+			assert_eq!(body, "not here");
+		}
+		_ => panic!(),
+	}
+}
@@ -12,6 +12,7 @@ fn no_params() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn trailing_slash() {
 	let path: Result<UsersListPath, _> = "/users/".parse();
 	match path {
@@ -21,6 +22,7 @@ fn trailing_slash() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn no_leading_slash() {
 	let path: Result<UsersListPath, _> = "users".parse();
 	match path {
@@ -42,6 +44,7 @@ fn one_param() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn invalid_param_type() {
 	let path: Result<UserDetailPath, _> = "/users/not_a_u64".parse();
 	match path {
@@ -51,6 +54,7 @@ fn invalid_param_type() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn one_param_no_leading_slash() {
 	let path: Result<UserDetailPath, _> = "users/4216".parse();
 	match path {
@@ -120,6 +124,7 @@ struct UsersListWithQuery {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn no_params_simple_query_required() {
 	let path: Result<UsersListWithQuery, _> = "/users".parse();
 	match path {
@@ -165,6 +170,7 @@ fn no_params_simple_query_missing_bool_field() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn no_params_simple_query_invalid_type() {
 	let path: Result<UsersListWithQuery, _> = "/users?offset=test".parse();
 	match path {
@@ -213,19 +219,78 @@ fn no_params_simple_query_url_decoding() {
 	);
 }
 
-// TODO - uncomment after https://github.com/samscott89/serde_qs/pull/18
-// #[test]
-// fn no_params_simple_query_url_decoding_plus_sign() {
-//     let path = UsersListWithQuery::from_str("/users?keyword=%2b").unwrap();
-//     assert_eq!(path, UsersListWithQuery { query: {
-//         UserListQuery {
-//             limit: None,
-//             offset: None,
-//             keyword: Some("+".to_string()),
-//             friends_only: false,
-//         }
-//     } });
-// }
+#[test]
+fn no_params_simple_query_url_decoding_plus_sign() {
+	let path: UsersListWithQuery = "/users?keyword=%2b".parse().unwrap();
+	assert_eq!(
+		path,
+		UsersListWithQuery {
+			query: UserListQuery {
+				limit: None,
+				offset: None,
+				keyword: Some("+".to_string()),
+				friends_only: false,
+			}
+		}
+	);
+}
+
+#[test]
+fn no_params_simple_query_plus_as_space() {
+	let path: UsersListWithQuery = "/users?keyword=one+two%2Bthree".parse().unwrap();
+	assert_eq!(path.query.keyword, Some("one two+three".to_string()));
+}
+
+#[test]
+fn no_params_simple_query_plus_round_trip() {
+	let path = UsersListWithQuery {
+		query: UserListQuery {
+			limit: None,
+			offset: None,
+			keyword: Some("a b+c&d=e%f".to_string()),
+			friends_only: false,
+		},
+	};
+
+	let rendered = path.to_string();
+	assert_eq!(
+		rendered,
+		"/users?keyword=a+b%2Bc%26d%3De%25f&friends_only=false"
+	);
+	assert_eq!(rendered.parse::<UsersListWithQuery>().unwrap(), path);
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/search")]
+struct PercentSpacesSearch {
+	#[query(spaces = "percent")]
+	query: UserListQuery,
+}
+
+#[test]
+fn percent_spaces_literal_plus() {
+	let path: PercentSpacesSearch = "/search?keyword=c++%20rocks".parse().unwrap();
+	assert_eq!(path.query.keyword, Some("c++ rocks".to_string()));
+}
+
+#[test]
+fn percent_spaces_round_trip() {
+	let path = PercentSpacesSearch {
+		query: UserListQuery {
+			limit: None,
+			offset: None,
+			keyword: Some("c++ rocks".to_string()),
+			friends_only: true,
+		},
+	};
+
+	let rendered = path.to_string();
+	assert_eq!(
+		rendered,
+		"/search?keyword=c%2B%2B%20rocks&friends_only=true"
+	);
+	assert_eq!(rendered.parse::<PercentSpacesSearch>().unwrap(), path);
+}
 
 #[derive(AppRoute, Debug, PartialEq)]
 #[route("/users/:user_id")]
@@ -266,6 +331,7 @@ fn one_param_optional_query_present() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn one_param_num_out_of_range() {
 	let path: Result<UserDetailExtraPath, _> = "/users/256".parse();
 	match path {
@@ -377,14 +443,13 @@ fn nested_query_3() {
 
 #[test]
 fn nested_query_4() {
-	let path: UserDetailNestedQueryPath = "/users/1024?address[apt_number]=101&address[country]=country_b&address[building][name]=Cool%20Building&address[building][number]=not_number".parse().unwrap();
-	assert_eq!(
-		path,
-		UserDetailNestedQueryPath {
-			user_id: 1024,
-			query: None,
-		}
-	);
+	// The query is meant for this field, so an invalid value is an error
+	// rather than a silent `None`
+	let path: Result<UserDetailNestedQueryPath, _> = "/users/1024?address[apt_number]=101&address[country]=country_b&address[building][name]=Cool%20Building&address[building][number]=not_number".parse();
+	match path {
+		Err(RouteParseErr::QueryParseErr(_)) => {}
+		_ => panic!(),
+	}
 }
 
 #[test]
@@ -437,14 +502,11 @@ fn vec_query_1() {
 
 #[test]
 fn vec_query_2() {
-	let path: UserDetailVecQueryPath = "/users/1024?friend_ids".parse().unwrap();
-	assert_eq!(
-		path,
-		UserDetailVecQueryPath {
-			user_id: 1024,
-			query: None,
-		}
-	);
+	let path: Result<UserDetailVecQueryPath, _> = "/users/1024?friend_ids".parse();
+	match path {
+		Err(RouteParseErr::QueryParseErr(_)) => {}
+		_ => panic!(),
+	}
 }
 
 #[test]
@@ -479,16 +541,12 @@ fn vec_query_4() {
 
 #[test]
 fn vec_query_5() {
-	let path: UserDetailVecQueryPath = "/users/1024?friend_ids[]=1&friend_ids[]=20&friend_ids=33"
-		.parse()
-		.unwrap();
-	assert_eq!(
-		path,
-		UserDetailVecQueryPath {
-			user_id: 1024,
-			query: None,
-		}
-	);
+	let path: Result<UserDetailVecQueryPath, _> =
+		"/users/1024?friend_ids[]=1&friend_ids[]=20&friend_ids=33".parse();
+	match path {
+		Err(RouteParseErr::QueryParseErr(_)) => {}
+		_ => panic!(),
+	}
 }
 
 #[test]
@@ -525,6 +583,52 @@ fn vec_query_7() {
 	);
 }
 
+#[test]
+fn vec_query_unrelated_keys() {
+	// None of the keys belong to VecQuery, so the query is left empty
+	let path: UserDetailVecQueryPath = "/users/1024?limit=10&offset=5".parse().unwrap();
+	assert_eq!(
+		path,
+		UserDetailVecQueryPath {
+			user_id: 1024,
+			query: None,
+		}
+	);
+}
+
+#[test]
+fn optional_query_unrelated_keys() {
+	let path: UserDetailExtraPath = "/users/8?foo=bar".parse().unwrap();
+	assert_eq!(
+		path,
+		UserDetailExtraPath {
+			user_id: 8,
+			query: None
+		}
+	);
+}
+
+#[test]
+fn optional_query_encoded_keys() {
+	let path: UserDetailExtraPath = "/users/8?%6Cimit=55".parse().unwrap();
+	assert_eq!(path.query.unwrap().limit, Some(55));
+
+	let path: Result<UserDetailExtraPath, _> = "/users/8?%6Cimit=abc".parse();
+	match path {
+		Err(RouteParseErr::QueryParseErr(_)) => {}
+		_ => panic!(),
+	}
+}
+
+#[test]
+fn optional_query_invalid_type() {
+	let path: Result<UserDetailExtraPath, _> = "/users/8?limit=lots".parse();
+	match path {
+		Err(RouteParseErr::QueryParseErr(_)) => {}
+		_ => panic!(),
+	}
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SortDirection {
@@ -580,15 +684,8 @@ fn test_only_question_mark() {
 		ExpiredSubmissionsPath {
 			project_id: "43".to_string(),
 			exam_id: 10,
-			query: Some(SubmissionsQuery {
-				column: None,
-				direction: None,
-				keyword: None,
-			}),
-			limit: Some(LimitOffsetQuery {
-				limit: None,
-				offset: None,
-			}),
+			query: None,
+			limit: None,
 		}
 	);
 }
@@ -642,6 +739,7 @@ struct UsersWildcardTrailingSlashRoute {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn wildcard_5() {
 	// The route pattern has an explicit '/' after 'users', so a path
 	// such as '/users' will not match this pattern
@@ -697,3 +795,174 @@ fn wildcard_7() {
 		}
 	);
 }
+
+#[test]
+fn canonical_string_sorts_keys() {
+	let a: UsersListWithQuery = "/users?offset=10&limit=20&keyword=a+b&friends_only=true"
+		.parse()
+		.unwrap();
+	let b: UsersListWithQuery = "/users?friends_only=true&keyword=a%20b&limit=20&offset=10"
+		.parse()
+		.unwrap();
+
+	assert_eq!(a, b);
+	assert_eq!(
+		a.canonical_string(),
+		"/users?friends_only=true&keyword=a+b&limit=20&offset=10"
+	);
+	assert_eq!(a.canonical_string(), b.canonical_string());
+}
+
+#[test]
+fn canonical_string_multiple_query_fields() {
+	let path: ExpiredSubmissionsPath =
+		"/p/43/exams/10/submissions_expired?offset=5&direction=asc&limit=10&column=name"
+			.parse()
+			.unwrap();
+
+	assert_eq!(
+		path.to_string(),
+		"/p/43/exams/10/submissions_expired?column=name&direction=asc&limit=10&offset=5"
+	);
+	assert_eq!(
+		path.canonical_string(),
+		"/p/43/exams/10/submissions_expired?column=name&direction=asc&limit=10&offset=5"
+	);
+
+	let path: ExpiredSubmissionsPath = "/p/43/exams/10/submissions_expired".parse().unwrap();
+	assert_eq!(
+		path.canonical_string(),
+		"/p/43/exams/10/submissions_expired"
+	);
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+struct CanonicalListQuery {
+	#[serde(default)]
+	sort: String,
+	#[serde(default)]
+	friends_only: bool,
+	page: Option<u32>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/groups/:group_id/users")]
+struct CanonicalListRoute {
+	group_id: u64,
+
+	#[query(skip_defaults = "canonical")]
+	query: CanonicalListQuery,
+}
+
+#[test]
+fn canonical_string_skip_defaults() {
+	let explicit: CanonicalListRoute = "/groups/1/users?sort=&friends_only=false&page=2"
+		.parse()
+		.unwrap();
+	let implicit: CanonicalListRoute = "/groups/1/users?page=2".parse().unwrap();
+
+	assert_eq!(explicit, implicit);
+	assert_eq!(explicit.canonical_string(), "/groups/1/users?page=2");
+	assert_eq!(implicit.canonical_string(), "/groups/1/users?page=2");
+
+	// Display still renders every field
+	assert_eq!(
+		implicit.to_string(),
+		"/groups/1/users?sort=&friends_only=false&page=2"
+	);
+
+	let all_defaults: CanonicalListRoute = "/groups/1/users?".parse().unwrap();
+	assert_eq!(all_defaults.canonical_string(), "/groups/1/users");
+	assert_eq!(
+		all_defaults
+			.to_string()
+			.parse::<CanonicalListRoute>()
+			.unwrap(),
+		all_defaults
+	);
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+struct FriendListQuery {
+	limit: u64,
+	keyword: Option<String>,
+	friends_only: bool,
+	friend_ids: Vec<u32>,
+}
+
+impl Default for FriendListQuery {
+	fn default() -> Self {
+		FriendListQuery {
+			limit: 20,
+			keyword: None,
+			friends_only: false,
+			friend_ids: Vec::new(),
+		}
+	}
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id/friends")]
+struct FriendListRoute {
+	user_id: u64,
+
+	#[query(skip_defaults)]
+	query: Option<FriendListQuery>,
+}
+
+#[test]
+fn skip_defaults_rendering() {
+	let path = FriendListRoute {
+		user_id: 5,
+		query: Some(FriendListQuery::default()),
+	};
+	assert_eq!(path.to_string(), "/users/5/friends");
+	assert_eq!(path.canonical_string(), "/users/5/friends");
+
+	// An all-default query can't be told apart from no query at all
+	assert_eq!(
+		path.to_string().parse::<FriendListRoute>().unwrap(),
+		FriendListRoute {
+			user_id: 5,
+			query: None,
+		}
+	);
+
+	let path = FriendListRoute {
+		user_id: 5,
+		query: Some(FriendListQuery {
+			limit: 50,
+			keyword: Some("bob".to_string()),
+			friends_only: false,
+			friend_ids: vec![20, 1],
+		}),
+	};
+	assert_eq!(
+		path.to_string(),
+		"/users/5/friends?limit=50&keyword=bob&friend_ids[0]=20&friend_ids[1]=1"
+	);
+	assert_eq!(path.to_string().parse::<FriendListRoute>().unwrap(), path);
+	assert_eq!(
+		path.canonical_string(),
+		"/users/5/friends?friend_ids[0]=20&friend_ids[1]=1&keyword=bob&limit=50"
+	);
+}
+
+#[test]
+fn skip_defaults_parsing_fills_defaults() {
+	let path: FriendListRoute = "/users/5/friends?friends_only=true".parse().unwrap();
+	assert_eq!(
+		path,
+		FriendListRoute {
+			user_id: 5,
+			query: Some(FriendListQuery {
+				limit: 20,
+				keyword: None,
+				friends_only: true,
+				friend_ids: Vec::new(),
+			}),
+		}
+	);
+	assert_eq!(path.to_string(), "/users/5/friends?friends_only=true");
+}
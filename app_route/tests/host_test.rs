@@ -0,0 +1,187 @@
+use app_route::{AppRoute, RouteParseErr};
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/dashboard/:section", host = ":tenant.example.com")]
+struct TenantDashboardRoute {
+	tenant: String,
+	section: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/settings", host = "admin.example.com")]
+struct AdminSettingsRoute {}
+
+#[test]
+fn host_param_from_absolute_url() {
+	let route =
+		TenantDashboardRoute::parse_url("https://acme.example.com/dashboard/billing").unwrap();
+	assert_eq!(
+		route,
+		TenantDashboardRoute {
+			tenant: "acme".to_string(),
+			section: "billing".to_string(),
+		}
+	);
+
+	// Host names are case-insensitive, and the port is ignored
+	let route =
+		TenantDashboardRoute::parse_url("https://ACME.Example.com:8443/dashboard/billing").unwrap();
+	assert_eq!(route.tenant, "ACME");
+}
+
+#[test]
+fn host_param_mismatch() {
+	match TenantDashboardRoute::parse_url("https://acme.example.org/dashboard/billing") {
+		Err(RouteParseErr::UnexpectedHost(Some(host))) => assert_eq!(host, "acme.example.org"),
+		_ => panic!(),
+	}
+
+	// Only a single label is captured
+	match TenantDashboardRoute::parse_url("https://a.b.example.com/dashboard/billing") {
+		Err(RouteParseErr::UnexpectedHost(_)) => {}
+		_ => panic!(),
+	}
+
+	match TenantDashboardRoute::parse_url("https://acme.example.com/elsewhere") {
+		Err(RouteParseErr::NoMatches) => {}
+		_ => panic!(),
+	}
+}
+
+#[test]
+fn host_param_requires_host() {
+	match "/dashboard/billing".parse::<TenantDashboardRoute>() {
+		Err(RouteParseErr::UnexpectedHost(None)) => {}
+		_ => panic!(),
+	}
+}
+
+#[test]
+fn static_host() {
+	assert_eq!(
+		AdminSettingsRoute::parse_url("https://admin.example.com/settings").unwrap(),
+		AdminSettingsRoute {}
+	);
+
+	match AdminSettingsRoute::parse_url("https://www.example.com/settings") {
+		Err(RouteParseErr::UnexpectedHost(Some(_))) => {}
+		_ => panic!(),
+	}
+
+	// Without a host there is nothing to check
+	assert_eq!(
+		"/settings".parse::<AdminSettingsRoute>().unwrap(),
+		AdminSettingsRoute {}
+	);
+}
+
+#[test]
+fn host_rendering() {
+	let route = TenantDashboardRoute {
+		tenant: "acme".to_string(),
+		section: "billing".to_string(),
+	};
+
+	assert_eq!(route.host(), Some("acme.example.com".to_string()));
+	assert_eq!(route.to_string(), "/dashboard/billing");
+	assert_eq!(
+		AdminSettingsRoute {}.host(),
+		Some("admin.example.com".to_string())
+	);
+}
+
+#[cfg(feature = "url")]
+#[test]
+fn host_to_url() {
+	use app_route::url::Url;
+	use std::convert::TryFrom;
+
+	let route = TenantDashboardRoute {
+		tenant: "acme".to_string(),
+		section: "billing".to_string(),
+	};
+
+	let base = Url::parse("https://example.com:8443/app/").unwrap();
+	let url = route.to_url(&base);
+	assert_eq!(
+		url.as_str(),
+		"https://acme.example.com:8443/app/dashboard/billing"
+	);
+
+	let url = Url::parse("https://acme.example.com/dashboard/billing").unwrap();
+	assert_eq!(TenantDashboardRoute::try_from(&url).unwrap(), route);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn host_from_http_request() {
+	use app_route::http::{Request, Uri};
+
+	let expected = TenantDashboardRoute {
+		tenant: "acme".to_string(),
+		section: "billing".to_string(),
+	};
+
+	let request = Request::get("/dashboard/billing")
+		.header("Host", "acme.example.com:8080")
+		.body(())
+		.unwrap();
+	assert_eq!(
+		TenantDashboardRoute::from_request(&request).unwrap(),
+		expected
+	);
+
+	let request = Request::get("https://acme.example.com/dashboard/billing")
+		.body(())
+		.unwrap();
+	assert_eq!(
+		TenantDashboardRoute::from_request(&request).unwrap(),
+		expected
+	);
+
+	let request = Request::get("/dashboard/billing").body(()).unwrap();
+	match TenantDashboardRoute::from_request(&request) {
+		Err(RouteParseErr::UnexpectedHost(None)) => {}
+		_ => panic!(),
+	}
+
+	let base: Uri = "https://example.com".parse().unwrap();
+	assert_eq!(
+		expected.to_absolute_uri(&base),
+		"https://acme.example.com/dashboard/billing"
+	);
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route_prefix("/v1")]
+enum TenantRouter {
+	Dashboard(TenantDashboardRoute),
+	Settings(AdminSettingsRoute),
+}
+
+#[test]
+fn host_in_canonical_string() {
+	let acme = TenantDashboardRoute {
+		tenant: "acme".to_string(),
+		section: "billing".to_string(),
+	};
+	let globex = TenantDashboardRoute {
+		tenant: "globex".to_string(),
+		section: "billing".to_string(),
+	};
+
+	assert_eq!(
+		acme.canonical_string(),
+		"//acme.example.com/dashboard/billing"
+	);
+	assert_ne!(acme.canonical_string(), globex.canonical_string());
+
+	assert_eq!(
+		TenantRouter::Dashboard(acme).canonical_string(),
+		"//acme.example.com/v1/dashboard/billing"
+	);
+	assert_eq!(
+		TenantRouter::Settings(AdminSettingsRoute {}).canonical_string(),
+		"//admin.example.com/v1/settings"
+	);
+}
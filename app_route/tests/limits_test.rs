@@ -0,0 +1,109 @@
+use app_route::{AppRoute, Limit, ParseLimits, RouteParseErr};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SearchQuery {
+	keyword: Option<String>,
+	friend_ids: Option<Vec<u32>>,
+	filter: Option<Filter>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Filter {
+	building: Option<Building>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Building {
+	name: Option<String>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/search/:tail*")]
+struct SearchRoute {
+	tail: String,
+
+	#[query]
+	query: Option<SearchQuery>,
+}
+
+fn untrusted() -> ParseLimits {
+	ParseLimits {
+		max_url_length: Some(64),
+		max_params: Some(3),
+		max_depth: Some(2),
+		max_array_index: Some(10),
+	}
+}
+
+fn assert_limit(path: &str, limit: Limit) {
+	match SearchRoute::from_str_with_limits(path, &untrusted()) {
+		Err(RouteParseErr::LimitExceeded(exceeded)) => assert_eq!(exceeded, limit),
+		other => panic!("{:?}", other),
+	}
+}
+
+#[test]
+fn within_limits() {
+	let path = SearchRoute::from_str_with_limits(
+		"/search/a/b?friend_ids[10]=1&filter[building][name]=x",
+		&untrusted(),
+	)
+	.unwrap();
+	assert_eq!(
+		path,
+		SearchRoute {
+			tail: "a/b".to_string(),
+			query: Some(SearchQuery {
+				keyword: None,
+				friend_ids: Some(vec![1]),
+				filter: Some(Filter {
+					building: Some(Building {
+						name: Some("x".to_string()),
+					}),
+				}),
+			}),
+		}
+	);
+}
+
+#[test]
+fn url_length() {
+	let path = format!("/search/{}", "a".repeat(100));
+	assert_limit(&path, Limit::UrlLength);
+
+	// The default limits don't restrict anything
+	let path: SearchRoute = path.parse().unwrap();
+	assert_eq!(path.tail.len(), 100);
+}
+
+#[test]
+fn max_params() {
+	assert_limit(
+		"/search/a?keyword=a&keyword=b&keyword=c&keyword=d",
+		Limit::Params,
+	);
+}
+
+#[test]
+fn max_depth() {
+	assert_limit("/search/a?filter[building][name][x]=1", Limit::Depth);
+}
+
+#[test]
+fn max_array_index() {
+	assert_limit("/search/a?friend_ids[11]=1", Limit::ArrayIndex);
+	assert_limit(
+		"/search/a?friend_ids[99999999999999999999999]=1",
+		Limit::ArrayIndex,
+	);
+}
+
+#[test]
+fn limits_checked_before_matching() {
+	// Even a URL which doesn't match the route reports the exceeded limit
+	match SearchRoute::from_str_with_limits(&format!("/other/{}", "a".repeat(100)), &untrusted()) {
+		Err(RouteParseErr::LimitExceeded(Limit::UrlLength)) => {}
+		_ => panic!(),
+	}
+}
@@ -0,0 +1,110 @@
+use app_route::{AppRoute, RouteParseErr};
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", method = "GET", method = "HEAD")]
+struct ShowUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", method = "DELETE")]
+struct DeleteUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id/avatar")]
+struct UserAvatarRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum UserRoute {
+	Show(ShowUserRoute),
+	Delete(DeleteUserRoute),
+	Avatar(UserAvatarRoute),
+}
+
+#[test]
+fn method_matches() {
+	let route = ShowUserRoute::from_method_and_str("GET", "/users/1").unwrap();
+	assert_eq!(route, ShowUserRoute { user_id: 1 });
+
+	let route = ShowUserRoute::from_method_and_str("HEAD", "/users/1").unwrap();
+	assert_eq!(route, ShowUserRoute { user_id: 1 });
+
+	// Without a method, any route matches
+	let route: DeleteUserRoute = "/users/1".parse().unwrap();
+	assert_eq!(route, DeleteUserRoute { user_id: 1 });
+}
+
+#[test]
+fn method_not_allowed() {
+	match ShowUserRoute::from_method_and_str("POST", "/users/1") {
+		Err(RouteParseErr::MethodNotAllowed { allowed }) => assert_eq!(allowed, ["GET", "HEAD"]),
+		_ => panic!(),
+	}
+
+	// Methods are case-sensitive
+	match DeleteUserRoute::from_method_and_str("delete", "/users/1") {
+		Err(RouteParseErr::MethodNotAllowed { allowed }) => assert_eq!(allowed, ["DELETE"]),
+		_ => panic!(),
+	}
+
+	// The path is matched first
+	match ShowUserRoute::from_method_and_str("POST", "/groups/1") {
+		Err(RouteParseErr::NoMatches) => {}
+		_ => panic!(),
+	}
+}
+
+#[test]
+fn router_dispatches_on_method() {
+	let route = UserRoute::from_method_and_str("DELETE", "/users/1").unwrap();
+	assert_eq!(route, UserRoute::Delete(DeleteUserRoute { user_id: 1 }));
+
+	let route = UserRoute::from_method_and_str("GET", "/users/1").unwrap();
+	assert_eq!(route, UserRoute::Show(ShowUserRoute { user_id: 1 }));
+
+	let route = UserRoute::from_method_and_str("PUT", "/users/1/avatar").unwrap();
+	assert_eq!(route, UserRoute::Avatar(UserAvatarRoute { user_id: 1 }));
+}
+
+#[test]
+fn router_merges_allowed_methods() {
+	match UserRoute::from_method_and_str("PUT", "/users/1") {
+		Err(RouteParseErr::MethodNotAllowed { allowed }) => {
+			assert_eq!(allowed, ["GET", "HEAD", "DELETE"])
+		}
+		_ => panic!(),
+	}
+
+	match UserRoute::from_method_and_str("PUT", "/users/abc/avatar") {
+		Err(RouteParseErr::ParamParseErr(_)) => {}
+		_ => panic!(),
+	}
+
+	match UserRoute::from_method_and_str("PUT", "/groups/1") {
+		Err(RouteParseErr::NoMatches) => {}
+		_ => panic!(),
+	}
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn method_from_request() {
+	let request = app_route::http::Request::delete("/users/7")
+		.body(())
+		.unwrap();
+	let route = UserRoute::from_request(&request).unwrap();
+	assert_eq!(route, UserRoute::Delete(DeleteUserRoute { user_id: 7 }));
+
+	let request = app_route::http::Request::post("/users/7").body(()).unwrap();
+
+	match UserRoute::from_request(&request) {
+		Err(RouteParseErr::MethodNotAllowed { allowed }) => {
+			assert_eq!(allowed, ["GET", "HEAD", "DELETE"])
+		}
+		_ => panic!(),
+	}
+}
@@ -0,0 +1,170 @@
+use app_route::{AppRoute, RouteParseErr};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct PageQuery {
+	page: u32,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct TokenQuery {
+	token: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/members/:member_id")]
+struct MemberRoute {
+	member_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/members")]
+struct MemberListRoute {
+	#[query]
+	query: PageQuery,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum GroupRoute {
+	Member(MemberRoute),
+	MemberList(MemberListRoute),
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/groups/:group_id")]
+struct GroupMemberRoute {
+	group_id: u64,
+
+	#[nested]
+	member: MemberRoute,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/groups/:group_id")]
+struct GroupPagesRoute {
+	group_id: u64,
+
+	#[nested]
+	route: GroupRoute,
+
+	#[query]
+	auth: Option<TokenQuery>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/dashboard", host = ":tenant.example.com")]
+struct TenantRoute {
+	tenant: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/")]
+struct RootRoute {
+	#[nested]
+	tenant: TenantRoute,
+}
+
+#[test]
+fn nested_route() {
+	let route: GroupMemberRoute = "/groups/1/members/2".parse().unwrap();
+	assert_eq!(
+		route,
+		GroupMemberRoute {
+			group_id: 1,
+			member: MemberRoute { member_id: 2 },
+		}
+	);
+	assert_eq!(route.to_string(), "/groups/1/members/2");
+
+	match "/groups/1/members".parse::<GroupMemberRoute>() {
+		Err(RouteParseErr::NoMatches) => {}
+		_ => panic!(),
+	}
+
+	match "/groups/1".parse::<GroupMemberRoute>() {
+		Err(RouteParseErr::NoMatches) => {}
+		_ => panic!(),
+	}
+
+	match "/groups/1/members/abc".parse::<GroupMemberRoute>() {
+		Err(RouteParseErr::ParamParseErr(_)) => {}
+		_ => panic!(),
+	}
+}
+
+#[test]
+fn nested_router_shares_query() {
+	let route: GroupPagesRoute = "/groups/1/members?page=2&token=abc".parse().unwrap();
+	assert_eq!(
+		route,
+		GroupPagesRoute {
+			group_id: 1,
+			route: GroupRoute::MemberList(MemberListRoute {
+				query: PageQuery { page: 2 },
+			}),
+			auth: Some(TokenQuery {
+				token: "abc".to_string(),
+			}),
+		}
+	);
+	assert_eq!(route.to_string(), "/groups/1/members?token=abc&page=2");
+	assert_eq!(
+		route.canonical_string(),
+		"/groups/1/members?page=2&token=abc"
+	);
+
+	let route: GroupPagesRoute = "/groups/1/members/5".parse().unwrap();
+	assert_eq!(
+		route,
+		GroupPagesRoute {
+			group_id: 1,
+			route: GroupRoute::Member(MemberRoute { member_id: 5 }),
+			auth: None,
+		}
+	);
+	assert_eq!(route.to_string(), "/groups/1/members/5");
+	assert_eq!(route.query_string(), None);
+}
+
+#[test]
+fn nested_host() {
+	let route = RootRoute::parse_url("https://acme.example.com/dashboard").unwrap();
+	assert_eq!(
+		route,
+		RootRoute {
+			tenant: TenantRoute {
+				tenant: "acme".to_string(),
+			},
+		}
+	);
+	assert_eq!(route.to_string(), "/dashboard");
+	assert_eq!(route.host(), Some("acme.example.com".to_string()));
+}
+
+#[test]
+fn nested_path_pattern() {
+	let pattern = app_route::Regex::new(&GroupPagesRoute::path_pattern()).unwrap();
+	assert!(pattern.is_match("/groups/1/members"));
+	assert!(pattern.is_match("/groups/1/members/2"));
+	assert!(!pattern.is_match("/groups/1"));
+	assert!(!pattern.is_match("/groups/1/members/2/3"));
+}
+
+#[test]
+fn nested_route_info() {
+	let patterns: Vec<_> = GroupPagesRoute::routes()
+		.into_iter()
+		.map(|route| route.pattern)
+		.collect();
+	assert_eq!(
+		patterns,
+		[
+			"/groups/:group_id/members/:member_id",
+			"/groups/:group_id/members"
+		]
+	);
+
+	let routes = RootRoute::routes();
+	assert_eq!(routes[0].pattern, "/dashboard");
+	assert_eq!(routes[0].host, Some(":tenant.example.com".to_string()));
+}
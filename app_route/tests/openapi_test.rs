@@ -0,0 +1,207 @@
+#![cfg(feature = "openapi")]
+
+use app_route::openapi::{json, openapi_path, OpenApi};
+use app_route::AppRoute;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Filters {
+	tag: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct UserListQuery {
+	limit: u32,
+	#[serde(rename = "q")]
+	search: Option<String>,
+	verified: bool,
+	filters: Option<Filters>,
+	#[serde(default)]
+	friends_only: bool,
+	big: Option<u128>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users", method = "GET")]
+struct UserListRoute {
+	#[query]
+	query: UserListQuery,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct SearchFilter {
+	name: Option<String>,
+	and: Option<Box<SearchFilter>>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/search")]
+struct SearchRoute {
+	#[query]
+	query: SearchFilter,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", method = "GET", method = "DELETE")]
+struct UserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/files/:path*")]
+struct FileRoute {
+	path: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route_prefix("/orgs/:org_id")]
+enum OrgRouter {
+	Users { org_id: i32, route: UserRoute },
+	UserList { org_id: i32, route: UserListRoute },
+}
+
+#[test]
+fn path_syntax() {
+	assert_eq!(openapi_path("/users/:user_id"), "/users/{user_id}");
+	assert_eq!(
+		openapi_path("/users/:user_id/files/:path*"),
+		"/users/{user_id}/files/{path}"
+	);
+	assert_eq!(openapi_path("/"), "/");
+}
+
+#[test]
+fn path_parameters() {
+	let operations = UserRoute::operations();
+	assert_eq!(operations.len(), 1);
+	assert_eq!(operations[0].path, "/users/{user_id}");
+	assert_eq!(operations[0].methods, vec!["GET", "DELETE"]);
+	assert_eq!(
+		operations[0].parameters,
+		vec![json!({
+			"name": "user_id",
+			"in": "path",
+			"required": true,
+			"schema": { "type": "integer", "format": "int64", "minimum": 0 },
+		})]
+	);
+
+	let operations = FileRoute::operations();
+	assert_eq!(operations[0].path, "/files/{path}");
+	assert_eq!(
+		operations[0].parameters[0]["schema"],
+		json!({ "type": "string" })
+	);
+}
+
+#[test]
+fn query_parameters() {
+	let operations = UserListRoute::operations();
+
+	assert_eq!(
+		operations[0].parameters,
+		vec![
+			json!({
+				"name": "limit",
+				"in": "query",
+				"required": true,
+				"schema": { "type": "integer", "format": "int32", "minimum": 0 },
+			}),
+			json!({
+				"name": "q",
+				"in": "query",
+				"required": false,
+				"schema": { "type": "string" },
+			}),
+			json!({
+				"name": "verified",
+				"in": "query",
+				"required": true,
+				"schema": { "type": "boolean" },
+			}),
+			json!({
+				"name": "filters",
+				"in": "query",
+				"required": false,
+				"style": "deepObject",
+				"explode": true,
+				"schema": {
+					"type": "object",
+					"properties": {
+						"tag": { "type": "array", "items": { "type": "string" } },
+					},
+					"required": ["tag"],
+				},
+			}),
+			json!({
+				"name": "friends_only",
+				"in": "query",
+				"required": false,
+				"schema": { "type": "boolean" },
+			}),
+			json!({
+				"name": "big",
+				"in": "query",
+				"required": false,
+				"schema": { "type": "integer", "minimum": 0 },
+			}),
+		]
+	);
+}
+
+#[test]
+fn recursive_query_parameters() {
+	let operations = SearchRoute::operations();
+
+	assert_eq!(
+		operations[0].parameters,
+		vec![
+			json!({
+				"name": "name",
+				"in": "query",
+				"required": false,
+				"schema": { "type": "string" },
+			}),
+			json!({
+				"name": "and",
+				"in": "query",
+				"required": false,
+				"schema": {},
+			}),
+		]
+	);
+}
+
+#[test]
+fn router_path_items() {
+	let paths = OrgRouter::path_items();
+
+	assert_eq!(
+		paths.keys().collect::<Vec<_>>(),
+		vec!["/orgs/{org_id}/users", "/orgs/{org_id}/users/{user_id}"]
+	);
+
+	let user = &paths["/orgs/{org_id}/users/{user_id}"];
+	assert!(user.get("get").is_some());
+	assert!(user.get("delete").is_some());
+	assert!(user.get("post").is_none());
+	assert_eq!(
+		user["get"]["responses"],
+		json!({ "default": { "description": "Default response" } })
+	);
+
+	let parameters = user["get"]["parameters"].as_array().unwrap();
+	assert_eq!(parameters.len(), 2);
+	assert_eq!(parameters[0]["name"], "org_id");
+	assert_eq!(
+		parameters[0]["schema"],
+		json!({ "type": "integer", "format": "int32" })
+	);
+	assert_eq!(parameters[1]["name"], "user_id");
+
+	let list_parameters = paths["/orgs/{org_id}/users"]["get"]["parameters"]
+		.as_array()
+		.unwrap();
+	assert_eq!(list_parameters.len(), 7);
+	assert_eq!(list_parameters[1]["name"], "limit");
+}
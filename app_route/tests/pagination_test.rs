@@ -0,0 +1,160 @@
+use app_route::pagination::{link_header, LinkHeader, Paginate, PaginateCursor, Pagination};
+use app_route::{AppRoute, Pagination};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize, Pagination)]
+#[pagination(limit = "limit", offset = "offset", default_limit = 20)]
+struct LimitOffsetQuery {
+	limit: Option<u64>,
+	offset: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, Pagination)]
+#[pagination(limit = "per_page", offset = "skip")]
+struct SkipQuery {
+	per_page: u32,
+	skip: u32,
+}
+
+impl Default for SkipQuery {
+	fn default() -> Self {
+		SkipQuery {
+			per_page: 50,
+			skip: 0,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize, Pagination)]
+#[pagination(cursor = "after")]
+struct CursorQuery {
+	limit: Option<u64>,
+	after: Option<String>,
+}
+
+#[derive(AppRoute, Clone, Debug, PartialEq)]
+#[route("/groups/:group_id/users")]
+#[app_route(builder)]
+struct UsersListRoute {
+	group_id: u64,
+
+	#[query]
+	query: LimitOffsetQuery,
+}
+
+#[derive(AppRoute, Clone, Debug, PartialEq)]
+#[route("/posts")]
+#[app_route(builder)]
+struct PostsRoute {
+	#[query]
+	query: Option<SkipQuery>,
+}
+
+#[derive(AppRoute, Clone, Debug, PartialEq)]
+#[route("/events")]
+#[app_route(builder)]
+struct EventsRoute {
+	#[query]
+	query: CursorQuery,
+}
+
+#[test]
+fn derive() {
+	let mut query = LimitOffsetQuery::default();
+	assert_eq!(query.limit(), 20);
+	assert_eq!(query.offset(), 0);
+
+	query.set_offset(40);
+	assert_eq!(query.offset, Some(40));
+
+	query.set_offset(0);
+	assert_eq!(query.offset, None);
+
+	let mut query = SkipQuery::default();
+	query.set_offset(100);
+	assert_eq!((query.limit(), query.offset()), (50, 100));
+
+	query.set_offset(u64::from(u32::MAX) + 1);
+	assert_eq!(query.skip, u32::MAX);
+}
+
+#[test]
+fn next_and_prev() {
+	let route = UsersListRoute::builder(1).build();
+	assert_eq!(route.page_number(), 1);
+	assert_eq!(route.prev_page(), None);
+
+	let next = route.next_page();
+	assert_eq!(next.to_string(), "/groups/1/users?offset=20");
+	assert_eq!(next.page_number(), 2);
+
+	let route = UsersListRoute::builder(1)
+		.query(|q| {
+			q.limit = Some(10);
+			q.offset = Some(5);
+		})
+		.build();
+	assert_eq!(route.page_number(), 2);
+	assert_eq!(
+		route.next_page().to_string(),
+		"/groups/1/users?limit=10&offset=15"
+	);
+	assert_eq!(
+		route.prev_page().unwrap().to_string(),
+		"/groups/1/users?limit=10"
+	);
+}
+
+#[test]
+fn pages() {
+	let route = PostsRoute::builder().build();
+	assert_eq!(route.to_string(), "/posts");
+	assert_eq!(route.page(3).to_string(), "/posts?per_page=50&skip=100");
+	assert_eq!(route.page(3).page_number(), 3);
+	assert_eq!(route.page(1).to_string(), "/posts?per_page=50&skip=0");
+	assert_eq!(route.page(0), route.page(1));
+}
+
+#[test]
+fn cursors() {
+	let route = EventsRoute::builder().build();
+	let next = route.cursor_page(Some("abc=="));
+	assert_eq!(next.to_string(), "/events?after=abc%3D%3D");
+	assert_eq!(next.cursor_page(None), route);
+}
+
+#[test]
+fn link_headers() {
+	let route = UsersListRoute::builder(1)
+		.query(|q| q.offset = Some(20))
+		.build();
+
+	assert_eq!(
+		link_header(&route, Some(45)).to_string(),
+		"</groups/1/users>; rel=\"first\", \
+		 </groups/1/users>; rel=\"prev\", \
+		 </groups/1/users?offset=40>; rel=\"next\", \
+		 </groups/1/users?offset=40>; rel=\"last\""
+	);
+
+	let last = route.next_page();
+	assert_eq!(
+		link_header(&last, Some(45)).to_string(),
+		"</groups/1/users>; rel=\"first\", \
+		 </groups/1/users?offset=20>; rel=\"prev\", \
+		 </groups/1/users?offset=40>; rel=\"last\""
+	);
+
+	let first = UsersListRoute::builder(1).build();
+	assert_eq!(
+		link_header(&first, None).to_string(),
+		"</groups/1/users>; rel=\"first\", </groups/1/users?offset=20>; rel=\"next\""
+	);
+
+	let header = LinkHeader::new().link("next", &"https://example.com/events?after=abc");
+	assert_eq!(
+		header.to_string(),
+		"<https://example.com/events?after=abc>; rel=\"next\""
+	);
+	assert!(LinkHeader::new().is_empty());
+}
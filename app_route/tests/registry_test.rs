@@ -0,0 +1,273 @@
+use app_route::registry::{Conflict, ConflictKind, Registry};
+use app_route::{AppRoute, RouteInfo};
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/me")]
+struct MeRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id")]
+struct UserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:name")]
+struct UserByNameRoute {
+	name: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id", method = "DELETE")]
+struct DeleteUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/files/:path*")]
+struct FileRoute {
+	path: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/files/readme")]
+struct ReadmeRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum GoodRouter {
+	Me(MeRoute),
+	User(UserRoute),
+	Readme(ReadmeRoute),
+	File(FileRoute),
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum BadRouter {
+	#[route(priority = 1)]
+	User(UserRoute),
+	Me(MeRoute),
+	UserByName(UserByNameRoute),
+}
+
+fn route(pattern: &str) -> RouteInfo {
+	RouteInfo {
+		pattern: pattern.to_string(),
+		host: None,
+		methods: vec![],
+		query: vec![],
+	}
+}
+
+fn kinds(registry: &Registry) -> Vec<(ConflictKind, String, String)> {
+	registry
+		.conflicts()
+		.into_iter()
+		.map(|c| (c.kind, c.first.pattern, c.second.pattern))
+		.collect()
+}
+
+#[test]
+fn specific_routes_first() {
+	assert_eq!(Registry::of::<GoodRouter>().validate(), Ok(()));
+	assert_eq!(Registry::of::<GoodRouter>().routes().len(), 4);
+}
+
+#[test]
+fn enum_router_conflicts() {
+	assert_eq!(
+		kinds(&Registry::of::<BadRouter>()),
+		[
+			(
+				ConflictKind::Shadowed,
+				"/users/:user_id".to_string(),
+				"/users/me".to_string()
+			),
+			(
+				ConflictKind::Duplicate,
+				"/users/:user_id".to_string(),
+				"/users/:name".to_string()
+			),
+		]
+	);
+
+	let conflicts = Registry::of::<BadRouter>().validate().unwrap_err();
+	assert_eq!(
+		conflicts[0].to_string(),
+		"`/users/me` is shadowed by `/users/:user_id`"
+	);
+	assert_eq!(
+		conflicts[1].to_string(),
+		"`/users/:name` duplicates `/users/:user_id`"
+	);
+}
+
+#[test]
+fn explicit_registry() {
+	let registry = Registry::new()
+		.register::<FileRoute>()
+		.register::<ReadmeRoute>()
+		.register_info(route("/users/:user_id/posts"))
+		.register_info(route("/:org/admin/posts"))
+		.register_info(route("/v:version/users"))
+		.register_info(route("/v1/users"));
+
+	assert_eq!(
+		kinds(&registry),
+		[
+			(
+				ConflictKind::Shadowed,
+				"/files/:path*".to_string(),
+				"/files/readme".to_string()
+			),
+			(
+				ConflictKind::Ambiguous,
+				"/files/:path*".to_string(),
+				"/:org/admin/posts".to_string()
+			),
+			(
+				ConflictKind::Ambiguous,
+				"/users/:user_id/posts".to_string(),
+				"/:org/admin/posts".to_string()
+			),
+			(
+				ConflictKind::Shadowed,
+				"/v:version/users".to_string(),
+				"/v1/users".to_string()
+			),
+		]
+	);
+}
+
+#[test]
+fn methods_and_hosts() {
+	let get = RouteInfo {
+		methods: vec!["GET".to_string()],
+		..route("/users/:user_id")
+	};
+	let registry = Registry::new()
+		.register_info(get)
+		.register::<DeleteUserRoute>();
+	assert_eq!(registry.validate(), Ok(()));
+
+	// A route accepting any method shadows one for a single method
+	let registry = Registry::new()
+		.register::<UserRoute>()
+		.register::<DeleteUserRoute>();
+	assert_eq!(
+		kinds(&registry),
+		[(
+			ConflictKind::Shadowed,
+			"/users/:user_id".to_string(),
+			"/users/:user_id".to_string()
+		)]
+	);
+
+	let get_post = RouteInfo {
+		methods: vec!["GET".to_string(), "POST".to_string()],
+		..route("/x")
+	};
+	let post = RouteInfo {
+		methods: vec!["POST".to_string()],
+		..route("/x")
+	};
+	let post_get = RouteInfo {
+		methods: vec!["POST".to_string(), "GET".to_string()],
+		..route("/x")
+	};
+	let registry = Registry::new()
+		.register_info(get_post.clone())
+		.register_info(post);
+	assert_eq!(
+		kinds(&registry),
+		[(ConflictKind::Shadowed, "/x".to_string(), "/x".to_string())]
+	);
+
+	let registry = Registry::new()
+		.register_info(get_post)
+		.register_info(post_get);
+	assert_eq!(
+		kinds(&registry),
+		[(ConflictKind::Duplicate, "/x".to_string(), "/x".to_string())]
+	);
+
+	let tenant = RouteInfo {
+		host: Some(":tenant.example.com".to_string()),
+		..route("/dashboard")
+	};
+	let admin = RouteInfo {
+		host: Some("admin.example.org".to_string()),
+		..route("/dashboard")
+	};
+	let registry = Registry::new()
+		.register_info(tenant.clone())
+		.register_info(admin)
+		.register_info(route("/dashboard"));
+	assert_eq!(registry.validate(), Ok(()));
+
+	let registry = Registry::new()
+		.register_info(route("/dashboard"))
+		.register_info(tenant.clone());
+	assert_eq!(
+		registry.conflicts(),
+		[Conflict {
+			kind: ConflictKind::Shadowed,
+			first: route("/dashboard"),
+			second: tenant,
+		}]
+	);
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id/friends")]
+struct FriendsRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/login")]
+struct LoginRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum SiteRouter {
+	Friends(FriendsRoute),
+	User(UserRoute),
+	File(FileRoute),
+	Login(LoginRoute),
+}
+
+fn suggestions(path: &str) -> Vec<(String, usize, usize)> {
+	Registry::of::<SiteRouter>()
+		.suggest(path)
+		.into_iter()
+		.map(|s| (s.route.pattern, s.distance, s.diverged_at))
+		.collect()
+}
+
+#[test]
+fn suggest() {
+	assert!("/users/5/friend".parse::<SiteRouter>().is_err());
+	assert_eq!(
+		suggestions("/users/5/friend?limit=10"),
+		[
+			("/users/:user_id/friends".to_string(), 1, 2),
+			("/users/:user_id".to_string(), 1, 2),
+		]
+	);
+
+	assert_eq!(
+		suggestions("/user/5/friends"),
+		[("/users/:user_id/friends".to_string(), 1, 0)]
+	);
+
+	assert_eq!(
+		suggestions("/users"),
+		[
+			("/users/:user_id".to_string(), 1, 1),
+			("/users/:user_id/friends".to_string(), 2, 1),
+		]
+	);
+
+	assert_eq!(suggestions("/files"), [("/files/:path*".to_string(), 1, 1)]);
+
+	assert_eq!(suggestions("/admin/settings"), []);
+}
@@ -0,0 +1,147 @@
+use app_route::{AppRoute, RouteParseErr};
+use serde::{Deserialize, Serialize};
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id")]
+struct UserRoute {
+	user_id: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct UserListQuery {
+	page: u32,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users")]
+struct UserListRoute {
+	#[query]
+	query: UserListQuery,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/repos/:repo_id")]
+struct RepoRoute {
+	repo_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route_prefix("/api/v2")]
+enum ApiRoute {
+	User(UserRoute),
+	UserList(UserListRoute),
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route_prefix("/orgs/:org_id")]
+enum OrgRoute {
+	User { org_id: String, route: UserRoute },
+	Repo { org_id: String, route: RepoRoute },
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum AppRouter {
+	Api(ApiRoute),
+	Org(OrgRoute),
+}
+
+#[test]
+fn static_prefix() {
+	let route: ApiRoute = "/api/v2/users/5".parse().unwrap();
+	assert_eq!(route, ApiRoute::User(UserRoute { user_id: 5 }));
+	assert_eq!(route.to_string(), "/api/v2/users/5");
+
+	let route: ApiRoute = "/api/v2/users?page=3".parse().unwrap();
+	assert_eq!(
+		route,
+		ApiRoute::UserList(UserListRoute {
+			query: UserListQuery { page: 3 }
+		})
+	);
+	assert_eq!(route.to_string(), "/api/v2/users?page=3");
+}
+
+#[test]
+fn static_prefix_mismatch() {
+	match "/users/5".parse::<ApiRoute>() {
+		Err(RouteParseErr::NoMatches) => {}
+		_ => panic!(),
+	}
+
+	match "/api/v2/repos/5".parse::<ApiRoute>() {
+		Err(RouteParseErr::NoMatches) => {}
+		_ => panic!(),
+	}
+
+	// Prefixes match whole segments
+	match "/api/v22/users/5".parse::<ApiRoute>() {
+		Err(RouteParseErr::NoMatches) => {}
+		_ => panic!(),
+	}
+}
+
+#[test]
+fn variant_errors_are_reported() {
+	// The path matches `UserListRoute`, which then fails on its query
+	match "/api/v2/users?page=abc".parse::<ApiRoute>() {
+		Err(RouteParseErr::QueryParseErr(_)) => {}
+		_ => panic!(),
+	}
+
+	match "/api/v2/users/abc".parse::<ApiRoute>() {
+		Err(RouteParseErr::ParamParseErr(_)) => {}
+		_ => panic!(),
+	}
+}
+
+#[test]
+fn prefix_params() {
+	let route: OrgRoute = "/orgs/acme/repos/12".parse().unwrap();
+	assert_eq!(
+		route,
+		OrgRoute::Repo {
+			org_id: "acme".to_string(),
+			route: RepoRoute { repo_id: 12 },
+		}
+	);
+	assert_eq!(route.to_string(), "/orgs/acme/repos/12");
+
+	let route = OrgRoute::User {
+		org_id: "acme".to_string(),
+		route: UserRoute { user_id: 3 },
+	};
+	assert_eq!(route.to_string(), "/orgs/acme/users/3");
+	assert_eq!(route.to_string().parse::<OrgRoute>().unwrap(), route);
+}
+
+#[test]
+fn routers_without_a_prefix() {
+	let route: AppRouter = "/orgs/acme/users/3".parse().unwrap();
+	assert_eq!(
+		route,
+		AppRouter::Org(OrgRoute::User {
+			org_id: "acme".to_string(),
+			route: UserRoute { user_id: 3 },
+		})
+	);
+
+	let route: AppRouter = "/api/v2/users/3".parse().unwrap();
+	assert_eq!(
+		route,
+		AppRouter::Api(ApiRoute::User(UserRoute { user_id: 3 }))
+	);
+	assert_eq!(route.to_string(), "/api/v2/users/3");
+}
+
+#[test]
+fn router_path_pattern() {
+	let pattern = app_route::Regex::new(&ApiRoute::path_pattern()).unwrap();
+	assert!(pattern.is_match("/api/v2/users/5"));
+	assert!(pattern.is_match("/api/v2/users"));
+	assert!(!pattern.is_match("/users/5"));
+
+	let pattern = app_route::Regex::new(&AppRouter::path_pattern()).unwrap();
+	assert!(pattern.is_match("/orgs/acme/repos/1"));
+	assert!(pattern.is_match("/api/v2/users"));
+	assert!(!pattern.is_match("/orgs/acme"));
+}
@@ -0,0 +1,114 @@
+use app_route::AppRoute;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct UserListQuery {
+	limit: Option<u64>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/groups/:group_id/users")]
+#[app_route(serde)]
+struct UsersListRoute {
+	group_id: u64,
+
+	#[query]
+	query: Option<UserListQuery>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/login")]
+struct LoginRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[app_route(serde)]
+enum AppRouter {
+	UsersList(UsersListRoute),
+	Login(LoginRoute),
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Config {
+	home: UsersListRoute,
+	links: Vec<AppRouter>,
+}
+
+#[test]
+fn serializes_as_strings() {
+	let config = Config {
+		home: UsersListRoute {
+			group_id: 1,
+			query: Some(UserListQuery { limit: Some(20) }),
+		},
+		links: vec![
+			AppRouter::Login(LoginRoute {}),
+			AppRouter::UsersList(UsersListRoute {
+				group_id: 2,
+				query: None,
+			}),
+		],
+	};
+
+	let json = serde_json::to_string(&config).unwrap();
+	assert_eq!(
+		json,
+		r#"{"home":"/groups/1/users?limit=20","links":["/login","/groups/2/users"]}"#
+	);
+
+	let parsed: Config = serde_json::from_str(&json).unwrap();
+	assert_eq!(parsed, config);
+}
+
+#[test]
+fn parse_errors() {
+	let error = serde_json::from_str::<Vec<AppRouter>>(r#"["/login", "/nowhere"]"#).unwrap_err();
+	assert_eq!(
+		error.to_string(),
+		r#"invalid route "/nowhere": NoMatches at line 1 column 22"#
+	);
+
+	let error = serde_json::from_str::<UsersListRoute>(r#"{"group_id": 1}"#).unwrap_err();
+	assert!(error
+		.to_string()
+		.starts_with("invalid type: map, expected a string"));
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/dashboard", host = ":tenant.example.com")]
+#[app_route(serde)]
+struct DashboardRoute {
+	tenant: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[app_route(serde)]
+enum TenantRouter {
+	Dashboard(DashboardRoute),
+	Login(LoginRoute),
+}
+
+#[test]
+fn host_routes_keep_their_host() {
+	let dashboard = DashboardRoute {
+		tenant: "acme".to_string(),
+	};
+
+	let json = serde_json::to_string(&dashboard).unwrap();
+	assert_eq!(json, r#""//acme.example.com/dashboard""#);
+	assert_eq!(
+		serde_json::from_str::<DashboardRoute>(&json).unwrap(),
+		dashboard
+	);
+
+	let routes = vec![
+		TenantRouter::Dashboard(dashboard),
+		TenantRouter::Login(LoginRoute {}),
+	];
+
+	let json = serde_json::to_string(&routes).unwrap();
+	assert_eq!(json, r#"["//acme.example.com/dashboard","/login"]"#);
+	assert_eq!(
+		serde_json::from_str::<Vec<TenantRouter>>(&json).unwrap(),
+		routes
+	);
+}
@@ -0,0 +1,181 @@
+#![cfg(feature = "signed")]
+
+use app_route::signed::SignatureErr;
+use app_route::{AppRoute, Limit, RouteParseErr};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const KEY: &[u8] = b"secret key";
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct DownloadQuery {
+	format: Option<String>,
+	user: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/downloads/:file_id")]
+struct DownloadRoute {
+	file_id: u64,
+
+	#[query]
+	query: DownloadQuery,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/unsubscribe/:token")]
+struct UnsubscribeRoute {
+	token: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/invoices/:invoice_id", host = ":tenant.example.com")]
+struct InvoiceRoute {
+	tenant: String,
+	invoice_id: u64,
+}
+
+fn at(secs: u64) -> SystemTime {
+	UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+fn download() -> DownloadRoute {
+	DownloadRoute {
+		file_id: 12,
+		query: DownloadQuery {
+			format: Some("zip".to_string()),
+			user: "alice".to_string(),
+		},
+	}
+}
+
+#[test]
+fn sign_and_verify() {
+	let signed = download().to_signed_string(KEY, at(2000));
+	assert!(signed.starts_with("/downloads/12?format=zip&user=alice&expires=2000&signature="));
+
+	let route = DownloadRoute::from_signed_str(&signed, KEY, at(1000)).unwrap();
+	assert_eq!(route, download());
+
+	let route = UnsubscribeRoute {
+		token: "abc".to_string(),
+	};
+	let signed = route.to_signed_string(KEY, at(2000));
+	assert!(signed.starts_with("/unsubscribe/abc?expires=2000&signature="));
+	assert_eq!(
+		UnsubscribeRoute::from_signed_str(&signed, KEY, at(1000)).unwrap(),
+		route
+	);
+}
+
+#[test]
+fn reordered_query() {
+	let signed = download().to_signed_string(KEY, at(2000));
+	let signature = signed.rsplit("signature=").next().unwrap();
+	let reordered = format!(
+		"/downloads/12?user=alice&format=zip&expires=2000&signature={}",
+		signature
+	);
+
+	assert_eq!(
+		DownloadRoute::from_signed_str(&reordered, KEY, at(1000)).unwrap(),
+		download()
+	);
+}
+
+#[test]
+fn bad_signature() {
+	let signed = download().to_signed_string(KEY, at(2000));
+
+	let tampered = signed.replace("/12?", "/13?");
+	assert!(matches!(
+		DownloadRoute::from_signed_str(&tampered, KEY, at(1000)),
+		Err(SignatureErr::BadSignature)
+	));
+
+	// Extending the expiry is tampering too, even once the link has expired
+	let extended = signed.replace("expires=2000", "expires=9000");
+	assert!(matches!(
+		DownloadRoute::from_signed_str(&extended, KEY, at(3000)),
+		Err(SignatureErr::BadSignature)
+	));
+
+	assert!(matches!(
+		DownloadRoute::from_signed_str(&signed, b"other key", at(1000)),
+		Err(SignatureErr::BadSignature)
+	));
+
+	assert!(matches!(
+		DownloadRoute::from_signed_str("/downloads/12?user=alice", KEY, at(1000)),
+		Err(SignatureErr::BadSignature)
+	));
+
+	let truncated = &signed[..signed.len() - 1];
+	assert!(matches!(
+		DownloadRoute::from_signed_str(truncated, KEY, at(1000)),
+		Err(SignatureErr::BadSignature)
+	));
+}
+
+#[test]
+fn expired() {
+	let signed = download().to_signed_string(KEY, at(2000));
+
+	match DownloadRoute::from_signed_str(&signed, KEY, at(2000)) {
+		Err(SignatureErr::Expired(expires)) => assert_eq!(expires, at(2000)),
+		other => panic!("expected Expired, got {:?}", other),
+	}
+}
+
+#[test]
+fn route_errors() {
+	let signed = download().to_signed_string(KEY, at(2000));
+	let signed = signed.replace("/downloads/12", "/uploads/12");
+
+	assert!(matches!(
+		DownloadRoute::from_signed_str(&signed, KEY, at(1000)),
+		Err(SignatureErr::Route(RouteParseErr::NoMatches))
+	));
+}
+
+#[test]
+fn host_pattern() {
+	let route = InvoiceRoute {
+		tenant: "acme".to_string(),
+		invoice_id: 7,
+	};
+	let signed = route.to_signed_string(KEY, at(2000));
+	assert!(signed.starts_with("//acme.example.com/invoices/7?expires=2000&signature="));
+
+	assert_eq!(
+		InvoiceRoute::from_signed_str(&signed, KEY, at(1000)).unwrap(),
+		route
+	);
+	assert_eq!(
+		InvoiceRoute::from_signed_str(&format!("https:{}", signed), KEY, at(1000)).unwrap(),
+		route
+	);
+
+	// A link signed for one tenant doesn't verify for another
+	let other_tenant = signed.replace("//acme.", "//globex.");
+	assert!(matches!(
+		InvoiceRoute::from_signed_str(&other_tenant, KEY, at(1000)),
+		Err(SignatureErr::BadSignature)
+	));
+}
+
+#[test]
+fn untrusted_limits() {
+	let route = UnsubscribeRoute {
+		token: "a".repeat(10_000),
+	};
+	let signed = route.to_signed_string(KEY, at(2000));
+
+	assert!(matches!(
+		UnsubscribeRoute::from_signed_str(&signed, KEY, at(1000)),
+		Err(SignatureErr::Route(RouteParseErr::LimitExceeded(
+			Limit::UrlLength
+		)))
+	));
+}
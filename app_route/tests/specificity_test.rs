@@ -0,0 +1,222 @@
+use app_route::registry::Specificity;
+use app_route::AppRoute;
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/me")]
+struct MeRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:name")]
+struct UserRoute {
+	name: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/v:version")]
+struct VersionRoute {
+	version: u32,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:path*")]
+struct UserFileRoute {
+	path: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum AppRouter {
+	File(UserFileRoute),
+	User(UserRoute),
+	Version(VersionRoute),
+	Me(MeRoute),
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum PriorityRouter {
+	Me(MeRoute),
+	#[route(priority = 1)]
+	User(UserRoute),
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum NestedRouter {
+	File(UserFileRoute),
+	App(AppRouter),
+}
+
+#[test]
+fn specificity() {
+	let static_segment = Specificity::of("/users/me");
+	let constrained = Specificity::of("/users/v:version");
+	let param = Specificity::of("/users/:name");
+	let wildcard = Specificity::of("/users/:path*");
+
+	assert!(static_segment > constrained);
+	assert!(constrained > param);
+	assert!(param > wildcard);
+	assert!(Specificity::of("/users/:name/posts") > param);
+	assert!(Specificity::of("/:org/users") > Specificity::of("/:org/:name"));
+	assert!(Specificity::of("/users") > Specificity::of("/files/:path*"));
+	assert_eq!(param, Specificity::of("/users/:id"));
+}
+
+#[test]
+fn most_specific_first() {
+	assert_eq!(
+		AppRouter::routes()
+			.iter()
+			.map(|route| route.pattern.as_str())
+			.collect::<Vec<_>>(),
+		[
+			"/users/me",
+			"/users/v:version",
+			"/users/:name",
+			"/users/:path*"
+		]
+	);
+
+	assert_eq!(
+		"/users/me".parse::<AppRouter>().unwrap(),
+		AppRouter::Me(MeRoute {})
+	);
+	assert_eq!(
+		"/users/v2".parse::<AppRouter>().unwrap(),
+		AppRouter::Version(VersionRoute { version: 2 })
+	);
+	assert_eq!(
+		"/users/bob".parse::<AppRouter>().unwrap(),
+		AppRouter::User(UserRoute {
+			name: "bob".to_string()
+		})
+	);
+	assert_eq!(
+		"/users/bob/avatar.png".parse::<AppRouter>().unwrap(),
+		AppRouter::File(UserFileRoute {
+			path: "bob/avatar.png".to_string()
+		})
+	);
+}
+
+#[test]
+fn priority_overrides_specificity() {
+	assert_eq!(
+		PriorityRouter::uri_templates(),
+		["/users/{name}", "/users/me"]
+	);
+	assert_eq!(
+		"/users/me".parse::<PriorityRouter>().unwrap(),
+		PriorityRouter::User(UserRoute {
+			name: "me".to_string()
+		})
+	);
+}
+
+#[test]
+fn nested_routers() {
+	// The routes of the nested router are merged with their sibling's
+	assert_eq!(
+		NestedRouter::routes()
+			.iter()
+			.map(|route| route.pattern.as_str())
+			.collect::<Vec<_>>(),
+		[
+			"/users/me",
+			"/users/v:version",
+			"/users/:name",
+			"/users/:path*",
+			"/users/:path*"
+		]
+	);
+	assert_eq!(
+		"/users/bob".parse::<NestedRouter>().unwrap(),
+		NestedRouter::App(AppRouter::User(UserRoute {
+			name: "bob".to_string()
+		}))
+	);
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/settings/notifications")]
+struct NotificationSettingsRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum AccountRouter {
+	Notifications(NotificationSettingsRoute),
+	User(UserRoute),
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum SiteRouter {
+	Account(AccountRouter),
+	Me(MeRoute),
+}
+
+#[test]
+fn nested_router_with_a_more_specific_route_elsewhere() {
+	// `Account` has the most specific route, but `Me` is more specific than
+	// the route of `Account` which matches `/users/me`
+	assert_eq!(
+		"/users/me".parse::<SiteRouter>().unwrap(),
+		SiteRouter::Me(MeRoute {})
+	);
+	assert_eq!(
+		"/users/bob".parse::<SiteRouter>().unwrap(),
+		SiteRouter::Account(AccountRouter::User(UserRoute {
+			name: "bob".to_string()
+		}))
+	);
+	assert_eq!(
+		"/users/settings/notifications"
+			.parse::<SiteRouter>()
+			.unwrap(),
+		SiteRouter::Account(AccountRouter::Notifications(NotificationSettingsRoute {}))
+	);
+	assert_eq!(
+		SiteRouter::uri_templates(),
+		[
+			"/users/settings/notifications",
+			"/users/me",
+			"/users/{name}"
+		]
+	);
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/items/:id")]
+struct ItemRoute<T>
+where
+	T: std::str::FromStr + std::fmt::Display,
+	T::Err: std::fmt::Display,
+{
+	id: T,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/items/new")]
+struct NewItemRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum ItemRouter<T>
+where
+	T: std::str::FromStr + std::fmt::Display,
+	T::Err: std::fmt::Display,
+{
+	Item(ItemRoute<T>),
+	New(NewItemRoute),
+}
+
+#[test]
+fn generic_routers() {
+	assert_eq!(
+		ItemRouter::<u32>::uri_templates(),
+		["/items/new", "/items/{id}"]
+	);
+	assert_eq!(
+		"/items/new".parse::<ItemRouter<u32>>().unwrap(),
+		ItemRouter::New(NewItemRoute {})
+	);
+	assert_eq!(
+		"/items/5".parse::<ItemRouter<u32>>().unwrap(),
+		ItemRouter::Item(ItemRoute { id: 5 })
+	);
+}
@@ -0,0 +1,114 @@
+use app_route::AppRoute;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct PageQuery {
+	limit: Option<u64>,
+	offset: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct SearchQuery {
+	#[serde(rename = "q")]
+	keyword: String,
+	limit: Option<u64>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/groups/{group_id}/users")]
+struct UsersListRoute {
+	group_id: u64,
+
+	#[query]
+	query: PageQuery,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/search")]
+struct SearchRoute {
+	#[query]
+	search: Option<SearchQuery>,
+
+	#[query]
+	page: Option<PageQuery>,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/files{/bucket,path*}")]
+struct FileRoute {
+	bucket: String,
+	path: String,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id")]
+struct UserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route_prefix("/orgs/{org_id}")]
+enum OrgRouter {
+	User { org_id: String, route: UserRoute },
+	Files { org_id: String, route: FileRoute },
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/v1")]
+struct VersionRoute {
+	#[nested]
+	route: UsersListRoute,
+
+	#[query]
+	search: Option<SearchQuery>,
+}
+
+#[test]
+fn export() {
+	assert_eq!(
+		UsersListRoute::uri_templates(),
+		["/groups/{group_id}/users{?limit,offset}"]
+	);
+	assert_eq!(SearchRoute::uri_templates(), ["/search{?q,limit,offset}"]);
+	assert_eq!(FileRoute::uri_templates(), ["/files/{bucket}/{+path}"]);
+	assert_eq!(
+		OrgRouter::uri_templates(),
+		[
+			"/orgs/{org_id}/users/{user_id}",
+			"/orgs/{org_id}/files/{bucket}/{+path}"
+		]
+	);
+	assert_eq!(
+		VersionRoute::uri_templates(),
+		["/v1/groups/{group_id}/users{?q,limit,offset}"]
+	);
+}
+
+#[test]
+fn template_routes() {
+	let route: UsersListRoute = "/groups/1/users?limit=10".parse().unwrap();
+	assert_eq!(
+		route,
+		UsersListRoute {
+			group_id: 1,
+			query: PageQuery {
+				limit: Some(10),
+				offset: None,
+			},
+		}
+	);
+	assert_eq!(route.to_string(), "/groups/1/users?limit=10");
+
+	let route: FileRoute = "/files/photos/2019/beach.jpg".parse().unwrap();
+	assert_eq!(
+		route,
+		FileRoute {
+			bucket: "photos".to_string(),
+			path: "2019/beach.jpg".to_string(),
+		}
+	);
+	assert_eq!(route.to_string(), "/files/photos/2019/beach.jpg");
+
+	let route: OrgRouter = "/orgs/acme/users/2".parse().unwrap();
+	assert_eq!(route.to_string(), "/orgs/acme/users/2");
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "tower")]
+
+use app_route::http::{header, Request, Response, StatusCode};
+use app_route::tower::{RouteLayer, RouteService};
+use app_route::{AppRoute, ParseLimits, RouteParseErr};
+use std::convert::Infallible;
+use tower::{service_fn, Layer, ServiceExt};
+
+#[derive(AppRoute, Clone, Debug, PartialEq)]
+#[route("/users/:user_id", method = "GET")]
+struct ShowUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Clone, Debug, PartialEq)]
+#[route("/users/:user_id", method = "DELETE")]
+struct DeleteUserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Clone, Debug, PartialEq)]
+enum UserRoute {
+	Show(ShowUserRoute),
+	Delete(DeleteUserRoute),
+}
+
+async fn handle(route: UserRoute, request: Request<()>) -> Result<Response<String>, Infallible> {
+	let body = match route {
+		UserRoute::Show(route) => format!("show {} {}", route.user_id, request.method()),
+		UserRoute::Delete(route) => format!("delete {}", route.user_id),
+	};
+
+	Ok(Response::new(body))
+}
+
+fn request(method: &str, uri: &str) -> Request<()> {
+	Request::builder().method(method).uri(uri).body(()).unwrap()
+}
+
+#[tokio::test]
+async fn dispatches_by_route() {
+	let service = RouteService::new(handle);
+
+	let response = service
+		.clone()
+		.oneshot(request("GET", "/users/1"))
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(response.body(), "show 1 GET");
+
+	let response = service
+		.oneshot(request("DELETE", "/users/2"))
+		.await
+		.unwrap();
+	assert_eq!(response.body(), "delete 2");
+}
+
+#[tokio::test]
+async fn default_error_responses() {
+	let service = RouteService::new(handle);
+
+	let response = service
+		.clone()
+		.oneshot(request("GET", "/groups/1"))
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	assert_eq!(response.body(), "");
+
+	let response = service
+		.clone()
+		.oneshot(request("GET", "/users/abc"))
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	let response = service.oneshot(request("POST", "/users/1")).await.unwrap();
+	assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+	assert_eq!(response.headers()[header::ALLOW], "GET, DELETE");
+}
+
+#[tokio::test]
+async fn custom_error_responses() {
+	let service = RouteService::new(handle).on_error(|error: RouteParseErr| {
+		let mut response = Response::new(format!("{}", error));
+		*response.status_mut() = StatusCode::IM_A_TEAPOT;
+		response
+	});
+
+	let response = service.oneshot(request("GET", "/groups/1")).await.unwrap();
+	assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+	assert_eq!(response.body(), "NoMatches");
+}
+
+#[tokio::test]
+async fn layer_inserts_route() {
+	let inner = service_fn(|request: Request<()>| async move {
+		let route = request.extensions().get::<UserRoute>().cloned();
+		Ok::<_, Infallible>(Response::new(format!("{:?}", route)))
+	});
+
+	let service = RouteLayer::<UserRoute, _>::new().layer(inner);
+
+	let response = service
+		.clone()
+		.oneshot(request("DELETE", "/users/3"))
+		.await
+		.unwrap();
+	assert_eq!(
+		response.body(),
+		"Some(Delete(DeleteUserRoute { user_id: 3 }))"
+	);
+
+	let response = service.oneshot(request("GET", "/groups/1")).await.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn parse_limits() {
+	// Untrusted limits apply by default
+	let uri = format!("/users/{}", "1".repeat(9000));
+	let response = RouteService::new(handle)
+		.oneshot(request("GET", &uri))
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	let limits = ParseLimits {
+		max_url_length: Some(8),
+		..ParseLimits::default()
+	};
+
+	let service = RouteService::new(handle).limits(limits.clone());
+
+	let response = service
+		.clone()
+		.oneshot(request("GET", "/users/1"))
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let response = service.oneshot(request("GET", "/users/10")).await.unwrap();
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	let inner =
+		service_fn(|_: Request<()>| async { Ok::<_, Infallible>(Response::new(String::new())) });
+	let service = RouteLayer::<UserRoute, _>::new()
+		.limits(limits)
+		.layer(inner);
+
+	let response = service.oneshot(request("GET", "/users/10")).await.unwrap();
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "typescript")]
+
+use app_route::query::SpaceEncoding;
+use app_route::typescript::{Helper, Param, TypeScript};
+use app_route::AppRoute;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+enum Sort {
+	#[default]
+	Name,
+	Age,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+struct Filters {
+	tags: Vec<String>,
+	verified: Option<bool>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+struct UserListQuery {
+	limit: Option<u32>,
+	#[serde(rename = "q")]
+	search: Option<String>,
+	sort: Sort,
+	filters: Filters,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/groups/:group_id/users")]
+struct UsersListRoute {
+	group_id: u64,
+
+	#[query(skip_defaults)]
+	query: UserListQuery,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/users/:user_id")]
+struct UserRoute {
+	user_id: u64,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/login")]
+struct LoginRoute {}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route_prefix("/orgs/:org_id")]
+enum OrgRouter {
+	User { org_id: String, route: UserRoute },
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+enum AppRouter {
+	UsersList(UsersListRoute),
+	User(UserRoute),
+	Org(OrgRouter),
+	Login(LoginRoute),
+}
+
+#[test]
+fn helpers() {
+	let helpers = OrgRouter::helpers();
+
+	assert_eq!(
+		helpers,
+		vec![Helper {
+			name: "UserRoute".to_string(),
+			scope: vec!["User".to_string()],
+			pattern: "/orgs/:org_id/users/:user_id".to_string(),
+			params: vec![Param::new("org_id", "String"), Param::new("user_id", "u64")],
+			queries: vec![],
+		}]
+	);
+
+	assert_eq!(helpers[0].params[0].ts_type, "string");
+	assert_eq!(helpers[0].params[1].ts_type, "number");
+
+	let query = &UsersListRoute::helpers()[0].queries[0];
+	assert_eq!(query.name, "query");
+	assert!(!query.optional);
+	assert_eq!(query.spaces, SpaceEncoding::Plus);
+	assert_eq!(query.defaults, Some("sort=Name".to_string()));
+	assert_eq!(
+		query.ts_type,
+		"{ limit?: number | null; q?: string | null; sort?: \"Name\" | \"Age\" | null; filters?: { tags: Array<string>; verified?: boolean | null } | null }"
+	);
+	assert_eq!(
+		query.shape,
+		"{ fields: [[\"limit\", null], [\"q\", null], [\"sort\", null], [\"filters\", { fields: [[\"tags\", { items: null }], [\"verified\", null]] }]] }"
+	);
+}
+
+#[test]
+fn functions() {
+	let typescript = AppRouter::typescript();
+
+	assert!(typescript.starts_with("// Generated by app_route. Do not edit.\n"));
+
+	assert!(typescript.contains("export function login(): string {\n\treturn `/login`;\n}\n"));
+
+	assert!(typescript.contains(
+		"export interface UsersListParams {\n\tgroup_id: number;\n\tquery: { limit?: number | null;"
+	));
+	assert!(typescript.contains(
+		"export function usersList(params: UsersListParams): string {\n\treturn withQuery(`/groups/${params[\"group_id\"]}/users`, [\n\t\tstripDefaults(encodeQuery(params[\"query\"], "
+	));
+}
+
+#[test]
+fn duplicate_names_are_qualified() {
+	let typescript = AppRouter::typescript();
+
+	assert!(typescript.contains(
+		"export function userUser(params: UserUserParams): string {\n\treturn `/users/${params[\"user_id\"]}`;\n}\n"
+	));
+	assert!(typescript.contains(
+		"export function orgUserUser(params: OrgUserUserParams): string {\n\treturn `/orgs/${params[\"org_id\"]}/users/${params[\"user_id\"]}`;\n}\n"
+	));
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct SearchQuery {
+	term: String,
+	#[serde(default)]
+	page: u32,
+}
+
+#[derive(AppRoute, Debug, PartialEq)]
+#[route("/search")]
+struct SearchRoute {
+	#[query]
+	query: SearchQuery,
+}
+
+#[test]
+fn defaulted_fields_are_optional() {
+	let query = &SearchRoute::helpers()[0].queries[0];
+	assert_eq!(query.ts_type, "{ term: string; page?: number | null }");
+}
@@ -0,0 +1,222 @@
+//! Builders for `#[app_route(builder)]` routes, and `#[derive(QueryBuilder)]`
+//! for the query structs they hold.
+
+use crate::{field_is_option, has_flag_attr, option_inner_type, type_name};
+use quote::quote;
+
+/// `Route::builder(...)`, taking every field except the `#[query]` fields in
+/// declaration order, and a builder whose query fields start out as their
+/// defaults. Also implements `QueryMut` for the route and its builder.
+///
+/// Returns the builder itself, which has to be nameable, separately from the
+/// `QueryMut` impls.
+pub fn derive_route_builder(
+	vis: &syn::Visibility,
+	name: &syn::Ident,
+	generics: &syn::Generics,
+	fields: &[syn::Field],
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+	let builder_name = syn::Ident::new(&format!("{}Builder", name), name.span());
+
+	let (query_fields, arg_fields): (Vec<_>, Vec<_>) = fields
+		.iter()
+		.partition(|f| has_flag_attr("query", &f.attrs));
+
+	let arg_idents: Vec<_> = arg_fields
+		.iter()
+		.map(|f| f.ident.clone().unwrap())
+		.collect();
+	let arg_types: Vec<_> = arg_fields.iter().map(|f| f.ty.clone()).collect();
+	let query_idents: Vec<_> = query_fields
+		.iter()
+		.map(|f| f.ident.clone().unwrap())
+		.collect();
+	let query_types: Vec<_> = query_fields.iter().map(|f| f.ty.clone()).collect();
+	let all_idents: Vec<_> = arg_idents.iter().chain(&query_idents).collect();
+
+	let all_idents = &all_idents;
+	let all_values = all_idents;
+	let arg_idents = &arg_idents;
+	let arg_types = &arg_types;
+	let query_idents = &query_idents;
+	let query_types = &query_types;
+	let arg_values = arg_idents;
+
+	// One `QueryMut` impl per distinct query type
+	let mut query_mut_fields: Vec<(&syn::Field, String)> = Vec::new();
+
+	for field in &query_fields {
+		let inner = option_inner_type(&field.ty);
+		let key = quote!(#inner).to_string();
+
+		if !query_mut_fields.iter().any(|(_, k)| *k == key) {
+			query_mut_fields.push((field, key));
+		}
+	}
+
+	let query_access: Vec<_> = query_mut_fields
+		.iter()
+		.map(|(field, _)| {
+			let ident = &field.ident;
+			let inner = option_inner_type(&field.ty);
+
+			let access = if field_is_option(field) {
+				quote! { self.#ident.get_or_insert_with(Default::default) }
+			} else {
+				quote! { &mut self.#ident }
+			};
+
+			(quote! { #inner }, access)
+		})
+		.collect();
+
+	let mut query_mut_impls = quote! {};
+
+	for target in &[
+		quote! { #name #ty_generics },
+		quote! { #builder_name #ty_generics },
+	] {
+		for (inner, access) in &query_access {
+			query_mut_impls.extend(quote! {
+				impl #impl_generics app_route::builder::QueryMut<#inner> for #target #where_clause {
+					fn query_mut(&mut self) -> &mut #inner {
+						#access
+					}
+				}
+			});
+		}
+	}
+
+	let query_setters = query_fields.iter().map(|field| {
+		let ident = &field.ident;
+		let inner = option_inner_type(&field.ty);
+		let doc = format!(
+			"Modifies the `{}` query, starting from its default.",
+			ident.as_ref().unwrap()
+		);
+
+		let access = if field_is_option(field) {
+			quote! { self.#ident.get_or_insert_with(Default::default) }
+		} else {
+			quote! { &mut self.#ident }
+		};
+
+		quote! {
+			#[doc = #doc]
+			#vis fn #ident<F: FnOnce(&mut #inner)>(mut self, modify: F) -> Self {
+				modify(#access);
+				self
+			}
+		}
+	});
+
+	let builder_doc = format!("Builds a [`{}`], see `{}::builder`.", name, name);
+	let constructor_doc = format!(
+		"Starts building a `{}`. The `#[query]` fields start out as their defaults.",
+		name
+	);
+
+	let builder = quote! {
+		#[doc = #builder_doc]
+		#vis struct #builder_name #impl_generics #where_clause {
+			#(#arg_idents: #arg_types,)*
+			#(#query_idents: #query_types,)*
+		}
+
+		impl #impl_generics #name #ty_generics #where_clause {
+			#[doc = #constructor_doc]
+			#vis fn builder(#(#arg_idents: #arg_types),*) -> #builder_name #ty_generics {
+				#builder_name {
+					#(#arg_idents: #arg_values,)*
+					#(#query_idents: Default::default(),)*
+				}
+			}
+		}
+
+		impl #impl_generics #builder_name #ty_generics #where_clause {
+			#(#query_setters)*
+
+			#vis fn build(self) -> #name #ty_generics {
+				#name {
+					#(#all_idents: self.#all_values),*
+				}
+			}
+		}
+	};
+
+	(builder, query_mut_impls)
+}
+
+/// `#[derive(QueryBuilder)]`, generating a `{Name}Builder` trait with a
+/// setter per field for anything implementing `QueryMut<Name>`. `Option`
+/// fields are set with their inner value.
+pub fn derive_query_builder(input: &syn::DeriveInput) -> proc_macro2::TokenStream {
+	let name = &input.ident;
+	let vis = &input.vis;
+	let trait_name = syn::Ident::new(&format!("{}Builder", name), name.span());
+
+	if !input.generics.params.is_empty() {
+		panic!("derive(QueryBuilder) doesn't support generic structs");
+	}
+
+	let fields: Vec<syn::Field> = match input.data {
+		syn::Data::Struct(ref data_struct) => match data_struct.fields {
+			syn::Fields::Named(ref named_fields) => named_fields.named.iter().cloned().collect(),
+			_ => panic!("Struct fields must be named"),
+		},
+		_ => panic!("QueryBuilder derive is only supported for structs"),
+	};
+
+	let setters = fields.iter().map(|field| {
+		let ident = &field.ident;
+		let doc = format!("Sets `{}`.", ident.as_ref().unwrap());
+		let ty = option_inner_type(&field.ty);
+
+		// Strings take anything which converts into them, but other types
+		// are taken as they are so that integer literals still infer
+		let (generics, value_ty, value) = if type_name(ty) == "String" {
+			(
+				quote! { <T: Into<#ty>> },
+				quote! { T },
+				quote! { value.into() },
+			)
+		} else {
+			(quote! {}, quote! { #ty }, quote! { value })
+		};
+
+		let value = if field_is_option(field) {
+			quote! { Some(#value) }
+		} else {
+			value
+		};
+
+		quote! {
+			#[doc = #doc]
+			fn #ident #generics(mut self, value: #value_ty) -> Self {
+				self.query_mut().#ident = #value;
+				self
+			}
+		}
+	});
+
+	let trait_doc = format!(
+		"Setters for the fields of [`{}`], on the query itself and on route builders holding one.",
+		name
+	);
+
+	quote! {
+		#[doc = #trait_doc]
+		#vis trait #trait_name: app_route::builder::QueryMut<#name> + Sized {
+			#(#setters)*
+		}
+
+		impl<B: app_route::builder::QueryMut<#name>> #trait_name for B {}
+
+		impl app_route::builder::QueryMut<#name> for #name {
+			fn query_mut(&mut self) -> &mut #name {
+				self
+			}
+		}
+	}
+}
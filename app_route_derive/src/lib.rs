@@ -2,12 +2,15 @@
 
 extern crate proc_macro;
 use proc_macro::TokenStream;
-use proc_macro2;
 use quote::quote;
 use regex::Regex;
 use std::collections::HashSet;
 use syn::{parse_macro_input, DeriveInput};
 
+mod builder;
+mod pagination;
+mod router;
+
 #[derive(Debug, PartialEq)]
 enum RouteToRegexError {
 	MissingLeadingForwardSlash,
@@ -15,6 +18,10 @@ enum RouteToRegexError {
 	InvalidIdentifier(String),
 	InvalidTrailingSlash,
 	CharactersAfterWildcard,
+	InvalidHostLabel(String),
+	WildcardInPrefix,
+	UnsupportedTemplate(String),
+	QueryInTemplate(String),
 }
 
 fn route_to_regex(route: &str) -> Result<(String, String), RouteToRegexError> {
@@ -23,7 +30,7 @@ fn route_to_regex(route: &str) -> Result<(String, String), RouteToRegexError> {
 		Static,
 		VarName(String),
 		WildcardFound,
-	};
+	}
 
 	if !route.is_ascii() {
 		return Err(RouteToRegexError::NonAsciiChars);
@@ -154,6 +161,224 @@ fn test_route_to_regex_invalid_ending() {
 	assert_eq!(regex, Err(RouteToRegexError::InvalidTrailingSlash));
 }
 
+/// Converts a route which other routes are mounted under, such as a
+/// `#[route_prefix]` or the route of a struct with a `#[nested]` field. The
+/// returned regex has no trailing `$`, so the rest of the path can follow it.
+fn prefix_to_regex(prefix: &str) -> Result<(String, String), RouteToRegexError> {
+	if prefix == "/" {
+		// Mounting under `/` is the same as not having a prefix at all
+		return Ok(("^".to_string(), "".to_string()));
+	}
+
+	let (regex, format_str) = route_to_regex(prefix)?;
+
+	if regex.contains(".*") {
+		return Err(RouteToRegexError::WildcardInPrefix);
+	}
+
+	Ok((regex[..(regex.len() - 1)].to_string(), format_str))
+}
+
+#[test]
+fn test_prefix_to_regex() {
+	let (regex, format_str) = prefix_to_regex("/orgs/:org_id").unwrap();
+	assert_eq!(regex, r"^/orgs/(?P<org_id>[^/]+)");
+	assert_eq!(format_str, "/orgs/{org_id}");
+
+	assert_eq!(prefix_to_regex("/"), Ok(("^".to_string(), "".to_string())));
+	assert_eq!(
+		prefix_to_regex("/files/:path*"),
+		Err(RouteToRegexError::WildcardInPrefix)
+	);
+}
+
+/// Converts an RFC 6570 URI template such as `/groups/{group_id}/users`
+/// into the `:param` route syntax, leaving routes without expressions as
+/// they are.
+///
+/// Simple `{var}` and path segment `{/var,other}` expansions become params,
+/// and reserved `{+var}` and exploded `{/var*}` expansions become a
+/// wildcard. `{?...}` and `{&...}` expansions are rejected, as the query
+/// keys come from the `#[query]` fields and can't be checked against them
+/// here.
+fn template_to_route(template: &str) -> Result<String, RouteToRegexError> {
+	let ident_regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9_]*$").unwrap();
+
+	let mut route = String::with_capacity(template.len());
+	let mut rest = template;
+
+	while let Some(start) = rest.find('{') {
+		route.push_str(&rest[..start]);
+
+		let end = rest[start..]
+			.find('}')
+			.map(|end| start + end)
+			.ok_or_else(|| RouteToRegexError::UnsupportedTemplate(rest[start..].to_string()))?;
+		let expression = &rest[start..=end];
+		let unsupported = || RouteToRegexError::UnsupportedTemplate(expression.to_string());
+
+		let body = &expression[1..(expression.len() - 1)];
+		let (operator, var_list) = match body.chars().next() {
+			Some(c @ '+') | Some(c @ '/') | Some(c @ '?') | Some(c @ '&') => (Some(c), &body[1..]),
+			_ => (None, body),
+		};
+
+		let vars: Vec<(&str, bool)> = var_list
+			.split(',')
+			.map(|var| match var.strip_suffix('*') {
+				Some(name) => (name, true),
+				None => (var, false),
+			})
+			.collect();
+
+		if vars.iter().any(|(name, _)| !ident_regex.is_match(name)) {
+			return Err(unsupported());
+		}
+
+		let last = vars.len() - 1;
+
+		match operator {
+			Some('?') | Some('&') => {
+				return Err(RouteToRegexError::QueryInTemplate(expression.to_string()))
+			}
+			None if vars.len() == 1 && !vars[0].1 => route.push_str(&format!(":{}", vars[0].0)),
+			Some('+') if vars.len() == 1 => route.push_str(&format!(":{}*", vars[0].0)),
+			Some('/') if vars[..last].iter().all(|(_, explode)| !explode) => {
+				for (i, (name, explode)) in vars.iter().enumerate() {
+					route.push_str(&format!("/:{}", name));
+
+					if i == last && *explode {
+						route.push('*');
+					}
+				}
+			}
+			_ => return Err(unsupported()),
+		}
+
+		rest = &rest[(end + 1)..];
+
+		// Params end at a segment boundary
+		if !(rest.is_empty() || rest.starts_with('/') || rest.starts_with('{')) {
+			return Err(unsupported());
+		}
+	}
+
+	route.push_str(rest);
+	Ok(route)
+}
+
+#[test]
+fn test_template_to_route() {
+	assert_eq!(
+		template_to_route("/groups/{group_id}/users"),
+		Ok("/groups/:group_id/users".to_string())
+	);
+	assert_eq!(
+		template_to_route("/groups{/group_id,user_id}"),
+		Ok("/groups/:group_id/:user_id".to_string())
+	);
+	assert_eq!(
+		template_to_route("/files/{+path}"),
+		Ok("/files/:path*".to_string())
+	);
+	assert_eq!(
+		template_to_route("/files{/dir,path*}"),
+		Ok("/files/:dir/:path*".to_string())
+	);
+	assert_eq!(
+		template_to_route("/users/:user_id"),
+		Ok("/users/:user_id".to_string())
+	);
+
+	for unsupported in &[
+		"/users/{user_id}.json",
+		"/users/{user_id,group_id}",
+		"/users{#section}",
+		"/users/{user_id:3}",
+		"/users/{user_id",
+	] {
+		assert!(
+			matches!(
+				template_to_route(unsupported),
+				Err(RouteToRegexError::UnsupportedTemplate(_))
+			),
+			"{} should be unsupported",
+			unsupported
+		);
+	}
+
+	for query in &[
+		"/groups/{group_id}/users{?limit,offset}",
+		"/users{?limit}{&offset}",
+		"/users{?limit}/more",
+	] {
+		assert!(
+			matches!(
+				template_to_route(query),
+				Err(RouteToRegexError::QueryInTemplate(_))
+			),
+			"{} should be rejected",
+			query
+		);
+	}
+}
+
+fn host_to_regex(host: &str) -> Result<(String, String), RouteToRegexError> {
+	if !host.is_ascii() {
+		return Err(RouteToRegexError::NonAsciiChars);
+	}
+
+	let ident_regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9_]*$").unwrap();
+	let label_regex = Regex::new(r"^[a-zA-Z0-9-]+$").unwrap();
+
+	let mut regex_labels = vec![];
+	let mut format_labels = vec![];
+
+	for label in host.split('.') {
+		if let Some(name) = label.strip_prefix(':') {
+			if !ident_regex.is_match(name) {
+				return Err(RouteToRegexError::InvalidIdentifier(name.to_string()));
+			}
+
+			regex_labels.push(format!("(?P<{}>[^.]+)", name));
+			format_labels.push(format!("{{{}}}", name));
+		} else if label_regex.is_match(label) {
+			regex_labels.push(label.to_string());
+			format_labels.push(label.to_string());
+		} else {
+			return Err(RouteToRegexError::InvalidHostLabel(label.to_string()));
+		}
+	}
+
+	// Host names are case-insensitive
+	let regex = format!("(?i)^{}$", regex_labels.join(r"\."));
+
+	Ok((regex, format_labels.join(".")))
+}
+
+#[test]
+fn test_host_to_regex() {
+	let (regex, format_str) = host_to_regex(":tenant.example.com").unwrap();
+	assert_eq!(regex, r"(?i)^(?P<tenant>[^.]+)\.example\.com$");
+	assert_eq!(format_str, "{tenant}.example.com");
+}
+
+#[test]
+fn test_host_to_regex_invalid_label() {
+	assert_eq!(
+		host_to_regex("admin..example.com"),
+		Err(RouteToRegexError::InvalidHostLabel("".to_string()))
+	);
+	assert_eq!(
+		host_to_regex("example.com:8080"),
+		Err(RouteToRegexError::InvalidHostLabel("com:8080".to_string()))
+	);
+	assert_eq!(
+		host_to_regex(":_tenant.example.com"),
+		Err(RouteToRegexError::InvalidIdentifier("_tenant".to_string()))
+	);
+}
+
 fn get_string_attr(name: &str, attrs: &[syn::Attribute]) -> Option<String> {
 	for attr in attrs {
 		let attr = attr.parse_meta();
@@ -176,26 +401,256 @@ fn has_flag_attr(name: &str, attrs: &[syn::Attribute]) -> bool {
 	for attr in attrs {
 		let attr = attr.parse_meta();
 
-		if let Ok(syn::Meta::Word(ref ident)) = attr {
-			if ident == name {
-				return true;
-			}
+		match attr {
+			Ok(syn::Meta::Word(ref ident)) if ident == name => return true,
+			Ok(syn::Meta::List(ref list)) if list.ident == name => return true,
+			_ => {}
 		}
 	}
 
 	false
 }
 
+/// Collects the options from an attribute such as
+/// `#[query(spaces = "percent", skip_defaults)]`. Bare words have no value.
+fn get_attr_options(name: &str, attrs: &[syn::Attribute]) -> Vec<(String, Option<syn::Lit>)> {
+	let mut options = Vec::new();
+
+	for attr in attrs {
+		let attr = attr.parse_meta();
+
+		if let Ok(syn::Meta::List(ref list)) = attr {
+			if list.ident == name {
+				for thing in &list.nested {
+					match thing {
+						syn::NestedMeta::Meta(syn::Meta::NameValue(name_value)) => {
+							options
+								.push((name_value.ident.to_string(), Some(name_value.lit.clone())));
+						}
+						syn::NestedMeta::Meta(syn::Meta::Word(ident)) => {
+							options.push((ident.to_string(), None));
+						}
+						_ => {}
+					}
+				}
+			}
+		}
+	}
+
+	options
+}
+
+fn lit_to_string(lit: &Option<syn::Lit>, key: &str) -> String {
+	match lit {
+		Some(syn::Lit::Str(str_lit)) => str_lit.value(),
+		_ => panic!("Expected a string literal for `{}`", key),
+	}
+}
+
+struct RouteOptions {
+	host: Option<String>,
+	methods: Vec<String>,
+	request: Option<syn::Type>,
+	response: Option<syn::Type>,
+}
+
+fn get_route_options(attrs: &[syn::Attribute]) -> RouteOptions {
+	let mut options = RouteOptions {
+		host: None,
+		methods: vec![],
+		request: None,
+		response: None,
+	};
+
+	let method_regex = Regex::new(r"^[!#$%&'*+.^_`|~0-9A-Za-z-]+$").unwrap();
+
+	for (key, lit) in get_attr_options("route", attrs) {
+		match key.as_str() {
+			"host" => options.host = Some(lit_to_string(&lit, &key)),
+			"method" => {
+				let method = lit_to_string(&lit, &key);
+
+				if !method_regex.is_match(&method) {
+					panic!("`{}` is not a valid HTTP method", method);
+				}
+
+				if !options.methods.contains(&method) {
+					options.methods.push(method);
+				}
+			}
+			"request" | "response" => {
+				let ty = syn::parse_str(&lit_to_string(&lit, &key))
+					.unwrap_or_else(|_| panic!("`{}` is not a valid type for `{}`", key, key));
+
+				if key == "request" {
+					options.request = Some(ty);
+				} else {
+					options.response = Some(ty);
+				}
+			}
+			_ => panic!("Unknown #[route] option `{}`", key),
+		}
+	}
+
+	options
+}
+
+fn capture_names(regex_str: &str) -> Vec<String> {
+	let regex = Regex::new(regex_str).expect("route attribute was not compiled into a valid regex");
+
+	regex
+		.capture_names()
+		.filter_map(|c_opt| c_opt.map(|c| c.to_string()))
+		.collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SkipDefaults {
+	Never,
+	Canonical,
+	Always,
+}
+
+struct QueryOptions {
+	spaces: proc_macro2::TokenStream,
+	skip_defaults: SkipDefaults,
+}
+
+fn get_query_options(field: &syn::Field) -> QueryOptions {
+	let mut options = QueryOptions {
+		spaces: quote! { app_route::query::SpaceEncoding::Plus },
+		skip_defaults: SkipDefaults::Never,
+	};
+
+	for (key, lit) in get_attr_options("query", &field.attrs) {
+		match key.as_str() {
+			"spaces" => {
+				options.spaces = match lit_to_string(&lit, &key).as_str() {
+					"plus" => quote! { app_route::query::SpaceEncoding::Plus },
+					"percent" => quote! { app_route::query::SpaceEncoding::Percent },
+					other => panic!(
+						"Unknown #[query(spaces = \"{}\")], expected \"plus\" or \"percent\"",
+						other
+					),
+				}
+			}
+			"skip_defaults" if lit.is_none() => {
+				options.skip_defaults = SkipDefaults::Always;
+			}
+			"skip_defaults" => {
+				options.skip_defaults = match lit_to_string(&lit, &key).as_str() {
+					"always" => SkipDefaults::Always,
+					"canonical" => SkipDefaults::Canonical,
+					other => panic!(
+						"Unknown #[query(skip_defaults = \"{}\")], expected \"always\" or \"canonical\"",
+						other
+					),
+				}
+			}
+			_ => panic!("Unknown #[query] option `{}`", key),
+		}
+	}
+
+	options
+}
+
+/// Generates an expression evaluating to the `Option<String>` encoding of a
+/// single `#[query]` field.
+fn query_field_to_string(field: &syn::Field, canonical: bool) -> proc_macro2::TokenStream {
+	let is_option = field_is_option(field);
+	let QueryOptions {
+		spaces,
+		skip_defaults,
+	} = get_query_options(field);
+	let f_ident = field.ident.clone().unwrap();
+
+	let skip = match skip_defaults {
+		SkipDefaults::Never => false,
+		SkipDefaults::Canonical => canonical,
+		SkipDefaults::Always => true,
+	};
+
+	let encode = if skip {
+		quote! { app_route::query::to_string_without_defaults }
+	} else {
+		quote! { app_route::query::to_string }
+	};
+
+	if is_option {
+		quote! {
+			self.#f_ident.as_ref().and_then(|q| #encode(q, #spaces))
+		}
+	} else {
+		quote! {
+			#encode(&self.#f_ident, #spaces)
+		}
+	}
+}
+
 fn get_struct_fields(data: &syn::Data) -> Vec<syn::Field> {
 	match data {
 		syn::Data::Struct(data_struct) => match data_struct.fields {
 			syn::Fields::Named(ref named_fields) => named_fields.named.iter().cloned().collect(),
 			_ => panic!("Struct fields must be named"),
 		},
-		_ => panic!("AppRoute derive is only supported for structs"),
+		_ => panic!("AppRoute derive is only supported for structs and enums"),
 	}
 }
 
+/// The name of the last segment of a type's path, e.g. `u64` for
+/// `std::primitive::u64`. Empty for types which aren't paths.
+fn type_name(ty: &syn::Type) -> String {
+	match ty {
+		syn::Type::Path(ref type_path) => type_path
+			.path
+			.segments
+			.iter()
+			.last()
+			.map(|segment| segment.ident.to_string())
+			.unwrap_or_default(),
+		_ => String::new(),
+	}
+}
+
+/// OpenAPI `Parameter` objects for the path params `names`, in order, typed
+/// by the matching fields.
+fn openapi_path_parameters(
+	names: &[String],
+	fields: &[syn::Field],
+) -> Vec<proc_macro2::TokenStream> {
+	names
+		.iter()
+		.map(|name| {
+			let field = fields
+				.iter()
+				.find(|f| f.ident.as_ref().map(|i| i == name).unwrap_or(false))
+				.expect("route params have matching fields");
+			let type_name = type_name(&field.ty);
+
+			quote! {
+				app_route::openapi::path_parameter(#name, #type_name)
+			}
+		})
+		.collect()
+}
+
+/// The `T` of an `Option<T>`, or the type itself if it isn't an `Option`.
+fn option_inner_type(ty: &syn::Type) -> &syn::Type {
+	if let syn::Type::Path(ref type_path) = ty {
+		if let Some(segment) = type_path.path.segments.iter().last() {
+			if segment.ident == "Option" {
+				if let syn::PathArguments::AngleBracketed(ref args) = segment.arguments {
+					if let Some(syn::GenericArgument::Type(ref inner)) = args.args.iter().next() {
+						return inner;
+					}
+				}
+			}
+		}
+	}
+
+	ty
+}
+
 fn field_is_option(field: &syn::Field) -> bool {
 	match field.ty {
 		syn::Type::Path(ref type_path) => type_path
@@ -209,12 +664,178 @@ fn field_is_option(field: &syn::Field) -> bool {
 	}
 }
 
-#[proc_macro_derive(AppRoute, attributes(route, query))]
+/// `TryFrom` implementations for the URL types of the enabled features, and
+/// extractor implementations for the enabled web frameworks.
+fn conversion_impls(name: &syn::Ident, generics: &syn::Generics) -> proc_macro2::TokenStream {
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+	let mut impls = quote! {};
+
+	if cfg!(feature = "http") {
+		impls.extend(quote! {
+			impl #impl_generics std::convert::TryFrom<&app_route::http::Uri> for #name #ty_generics #where_clause {
+				type Error = app_route::RouteParseErr;
+
+				fn try_from(uri: &app_route::http::Uri) -> Result<Self, Self::Error> {
+					app_route::uri::from_http_uri(uri)
+				}
+			}
+		});
+	}
+
+	if cfg!(feature = "actix-web") {
+		impls.extend(quote! {
+			impl #impl_generics app_route::actix::FromRequest for #name #ty_generics #where_clause {
+				type Error = app_route::actix::RouteRejection;
+				type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+				fn from_request(
+					request: &app_route::actix::Request,
+					_payload: &mut app_route::actix::Payload,
+				) -> Self::Future {
+					std::future::ready(app_route::actix::from_request(request))
+				}
+			}
+		});
+	}
+
+	if cfg!(feature = "axum") {
+		let mut extractor_generics = generics.clone();
+		extractor_generics
+			.params
+			.push(syn::parse_quote!(AppRouteState: Send + Sync));
+		let (extractor_impl_generics, _, _) = extractor_generics.split_for_impl();
+
+		impls.extend(quote! {
+			impl #extractor_impl_generics app_route::axum::FromRequestParts<AppRouteState> for #name #ty_generics #where_clause {
+				type Rejection = app_route::axum::RouteRejection;
+
+				async fn from_request_parts(
+					parts: &mut app_route::http::request::Parts,
+					_state: &AppRouteState,
+				) -> Result<Self, Self::Rejection> {
+					app_route::axum::from_request_parts(parts)
+				}
+			}
+		});
+	}
+
+	if cfg!(feature = "url") {
+		impls.extend(quote! {
+			impl #impl_generics std::convert::TryFrom<&app_route::url::Url> for #name #ty_generics #where_clause {
+				type Error = app_route::RouteParseErr;
+
+				fn try_from(url: &app_route::url::Url) -> Result<Self, Self::Error> {
+					app_route::uri::from_url(url)
+				}
+			}
+		});
+	}
+
+	impls
+}
+
+/// Options given in `#[app_route(...)]`, such as `#[app_route(serde)]`.
+struct ContainerOptions {
+	builder: bool,
+	serde: bool,
+}
+
+fn get_container_options(attrs: &[syn::Attribute]) -> ContainerOptions {
+	let mut options = ContainerOptions {
+		builder: false,
+		serde: false,
+	};
+
+	for (key, lit) in get_attr_options("app_route", attrs) {
+		match key.as_str() {
+			"builder" if lit.is_none() => options.builder = true,
+			"serde" if lit.is_none() => options.serde = true,
+			_ => panic!("Unknown #[app_route] option `{}`", key),
+		}
+	}
+
+	options
+}
+
+/// `Serialize` and `Deserialize` implementations for `#[app_route(serde)]`,
+/// going through `Display` and `FromStr` so routes are stored as strings.
+fn serde_impls(
+	name: &syn::Ident,
+	generics: &syn::Generics,
+	attrs: &[syn::Attribute],
+) -> proc_macro2::TokenStream {
+	if !get_container_options(attrs).serde {
+		return quote! {};
+	}
+
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+	let mut de_generics = generics.clone();
+	de_generics.params.insert(0, syn::parse_quote!('de));
+	let (de_impl_generics, _, _) = de_generics.split_for_impl();
+
+	quote! {
+		impl #impl_generics app_route::serde::Serialize for #name #ty_generics #where_clause {
+			fn serialize<S: app_route::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+				// Display leaves out the host, so routes with one are
+				// serialized as scheme-relative URLs
+				match app_route::AppRoute::host(self) {
+					Some(host) => serializer.collect_str(&format_args!("//{}{}", host, self)),
+					None => serializer.collect_str(self),
+				}
+			}
+		}
+
+		impl #de_impl_generics app_route::serde::Deserialize<'de> for #name #ty_generics #where_clause {
+			fn deserialize<D: app_route::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+				let route = <String as app_route::serde::Deserialize>::deserialize(deserializer)?;
+
+				<Self as app_route::AppRoute>::parse_url(&route).map_err(|e| {
+					<D::Error as app_route::serde::de::Error>::custom(format_args!("invalid route {:?}: {}", route, e))
+				})
+			}
+		}
+	}
+}
+
+#[proc_macro_derive(QueryBuilder)]
+pub fn query_builder_derive(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	builder::derive_query_builder(&input).into()
+}
+
+#[proc_macro_derive(Pagination, attributes(pagination))]
+pub fn pagination_derive(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	pagination::derive_pagination(&input).into()
+}
+
+#[proc_macro_derive(AppRoute, attributes(route, query, route_prefix, nested, app_route))]
 pub fn app_route_derive(input: TokenStream) -> TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
 
+	if let syn::Data::Enum(ref data_enum) = input.data {
+		return router::derive_enum_router(&input, data_enum).into();
+	}
+
 	let struct_fields = get_struct_fields(&input.data);
 
+	let (builder, query_mut_impls) = if get_container_options(&input.attrs).builder {
+		builder::derive_route_builder(&input.vis, &input.ident, &input.generics, &struct_fields)
+	} else {
+		(quote! {}, quote! {})
+	};
+
+	let (nested_fields, struct_fields): (Vec<_>, Vec<_>) = struct_fields
+		.into_iter()
+		.partition(|f| has_flag_attr("nested", &f.attrs));
+
+	if nested_fields.len() > 1 {
+		panic!("Only one field can be marked with #[nested]");
+	}
+
+	let nested_field = nested_fields.into_iter().next();
+
 	let (route_fields, query_fields): (Vec<_>, Vec<_>) = struct_fields
 		.into_iter()
 		.partition(|f| !has_flag_attr("query", &f.attrs));
@@ -228,17 +849,70 @@ pub fn app_route_derive(input: TokenStream) -> TokenStream {
 	let url_route = route_string.expect(
 		"derive(AppRoute) requires a #[route(\"/your/route/here\")] attribute on the struct",
 	);
+	let url_route = template_to_route(&url_route)
+		.expect("Could not convert the URI template in the route attribute");
+
+	let (route_regex_str, format_str, path_pattern) = match nested_field {
+		Some(ref nested_field) => {
+			// The nested route matches the rest of the path, which is
+			// captured by the last group
+			let (prefix_regex_str, format_str) = prefix_to_regex(&url_route)
+				.expect("Could not convert route attribute to a valid regex for a #[nested] field");
+			let nested_ty = &nested_field.ty;
+
+			let path_pattern = quote! {
+				app_route::pattern::nest(#prefix_regex_str, &[<#nested_ty as app_route::AppRoute>::path_pattern()])
+			};
+
+			(
+				format!("{}(/.*)$", prefix_regex_str),
+				format_str,
+				path_pattern,
+			)
+		}
+		None => {
+			let (route_regex_str, format_str) = route_to_regex(&url_route)
+				.expect("Could not convert route attribute to a valid regex");
 
-	let (route_regex_str, format_str) =
-		route_to_regex(&url_route).expect("Could not convert route attribute to a valid regex");
+			let path_pattern = quote! {
+				#route_regex_str.to_string()
+			};
+
+			(route_regex_str, format_str, path_pattern)
+		}
+	};
+
+	let RouteOptions {
+		host,
+		methods,
+		request,
+		response,
+	} = get_route_options(&input.attrs);
+
+	let host_regex = host.as_ref().map(|host| {
+		host_to_regex(host).expect("Could not convert host attribute to a valid regex")
+	});
 
 	// Validate route_regex and make sure struct and route have matching fields
-	let route_regex =
-		Regex::new(&route_regex_str).expect("route attribute was not compiled into a valid regex");
+	let path_capture_names = capture_names(&route_regex_str);
+	let host_capture_names = host_regex
+		.as_ref()
+		.map(|(host_regex_str, _)| capture_names(host_regex_str))
+		.unwrap_or_default();
+
+	for host_capture_name in &host_capture_names {
+		if path_capture_names.contains(host_capture_name) {
+			panic!(
+				"`{}` is used in both the host and the path of the route",
+				host_capture_name
+			);
+		}
+	}
 
-	let regex_capture_names_set: HashSet<String> = route_regex
-		.capture_names()
-		.filter_map(|c_opt| c_opt.map(|c| c.to_string()))
+	let regex_capture_names_set: HashSet<String> = path_capture_names
+		.iter()
+		.chain(host_capture_names.iter())
+		.cloned()
 		.collect();
 	let field_names_set: HashSet<String> = route_fields
 		.clone()
@@ -251,48 +925,48 @@ pub fn app_route_derive(input: TokenStream) -> TokenStream {
 		let missing_from_struct = regex_capture_names_set.difference(&field_names_set);
 
 		let error_msg = format!("\nFields in struct missing from route pattern: {:?}\nFields in route missing from struct: {:?}", missing_from_route, missing_from_struct);
-		panic!(error_msg);
+		panic!("{}", error_msg);
 	}
 
+	let (host_fields, path_fields): (Vec<_>, Vec<_>) = route_fields
+		.iter()
+		.cloned()
+		.partition(|f| host_capture_names.contains(&f.ident.as_ref().unwrap().to_string()));
+
 	let route_field_assignments = route_fields.clone().into_iter().map(|f| {
 		let f_ident = f.ident.unwrap();
 		let f_ident_str = f_ident.to_string();
 
+		let captures = if host_capture_names.contains(&f_ident_str) {
+			quote! { host_captures }
+		} else {
+			quote! { captures }
+		};
+
 		quote! {
-			#f_ident: captures[#f_ident_str].parse().map_err(|e| {
+			#f_ident: #captures[#f_ident_str].parse().map_err(|e| {
 				RouteParseErr::ParamParseErr(std::string::ToString::to_string(&e))
 			})?
 		}
 	});
 
 	let query_field_assignments = query_fields.clone().into_iter().map(|f| {
-        let is_option = field_is_option(&f);
-        let f_ident = f.ident.unwrap();
-
-        if is_option {
-            quote! {
-                #f_ident: query_string.and_then(|q| qs::from_str(q).ok())
-            }
-        } else {
-            quote! {
-                #f_ident: qs::from_str(query_string.ok_or(RouteParseErr::NoQueryString)?).map_err(|e| RouteParseErr::QueryParseErr(e.description().to_string()))?
-            }
-        }
-    });
-
-	let route_field_parsers = quote! {
-		#(
-			#route_field_assignments
-		),*
-	};
+		let is_option = field_is_option(&f);
+		let QueryOptions { spaces, .. } = get_query_options(&f);
+		let f_ident = f.ident.unwrap();
 
-	let query_field_parsers = quote! {
-		#(
-			#query_field_assignments
-		),*
-	};
+		if is_option {
+			quote! {
+				#f_ident: app_route::query::from_str_optional(query_string, #spaces, limits)?
+			}
+		} else {
+			quote! {
+				#f_ident: app_route::query::from_str(query_string.ok_or(RouteParseErr::NoQueryString)?, #spaces, limits)?
+			}
+		}
+	});
 
-	let format_args = route_fields.clone().into_iter().map(|f| {
+	let format_args = path_fields.into_iter().map(|f| {
 		let f_ident = f.ident.unwrap();
 
 		quote! {
@@ -306,74 +980,299 @@ pub fn app_route_derive(input: TokenStream) -> TokenStream {
 		),*
 	};
 
-	let query_field_to_string_statements = query_fields.into_iter().map(|f| {
-		let is_option = field_is_option(&f);
+	let host_format_args = host_fields.into_iter().map(|f| {
 		let f_ident = f.ident.unwrap();
 
-		if is_option {
+		quote! {
+			#f_ident = self.#f_ident
+		}
+	});
+
+	let (host_matching, host_impl) = match host_regex {
+		Some((host_regex_str, host_format_str)) => {
+			let host_matching = if host_capture_names.is_empty() {
+				quote! {
+					if let Some(host) = host {
+						if !(*HOST_REGEX).is_match(host) {
+							return Err(RouteParseErr::UnexpectedHost(Some(host.to_string())));
+						}
+					}
+				}
+			} else {
+				quote! {
+					let host = host.ok_or(RouteParseErr::UnexpectedHost(None))?;
+					let host_captures = (*HOST_REGEX).captures(host).ok_or_else(|| {
+						RouteParseErr::UnexpectedHost(Some(host.to_string()))
+					})?;
+				}
+			};
+
+			let host_matching = quote! {
+				app_route::lazy_static! {
+					static ref HOST_REGEX: app_route::Regex = app_route::Regex::new(#host_regex_str).expect("Failed to compile regex");
+				}
+
+				#host_matching
+			};
+
+			let host_impl = quote! {
+				fn host(&self) -> Option<String> {
+					Some(format!(
+						#host_format_str,
+						#(#host_format_args),*
+					))
+				}
+			};
+
+			(host_matching, host_impl)
+		}
+		None => {
+			let host_impl = match nested_field {
+				Some(ref nested_field) => {
+					let f_ident = &nested_field.ident;
+
+					quote! {
+						fn host(&self) -> Option<String> {
+							self.#f_ident.host()
+						}
+					}
+				}
+				None => quote! {},
+			};
+
+			(quote! { let _ = host; }, host_impl)
+		}
+	};
+
+	let query_types: Vec<_> = query_fields.iter().map(|f| &f.ty).collect();
+	let query_types = &query_types;
+	let route_query = quote! {
+		{
+			#[allow(unused_mut)]
+			let mut query: Vec<String> = Vec::new();
+			#(
+				query.extend(app_route::query::field_names::<#query_types>().into_iter().map(String::from));
+			)*
+			query
+		}
+	};
+
+	let routes_impl = match nested_field {
+		Some(ref nested_field) => {
+			let nested_ty = &nested_field.ty;
+			let route_methods = &methods;
+			let mount_prefix = if url_route == "/" {
+				""
+			} else {
+				url_route.as_str()
+			};
+			let mount_host = match host {
+				Some(ref host) => quote! { Some(#host) },
+				None => quote! { None },
+			};
+
 			quote! {
-				self.#f_ident.as_ref().and_then(|q| qs::to_string(&q).ok())
+				let query: Vec<String> = #route_query;
+
+				<#nested_ty as app_route::AppRoute>::routes()
+					.into_iter()
+					.map(|route| {
+						let mut route = route.mount(#mount_prefix, #mount_host, &[#(#route_methods),*]);
+						route.query.splice(0..0, query.iter().cloned());
+						route
+					})
+					.collect()
 			}
-		} else {
+		}
+		None => {
+			let route_methods = &methods;
+			let host = match host {
+				Some(ref host) => quote! { Some(#host.to_string()) },
+				None => quote! { None },
+			};
+
 			quote! {
-				qs::to_string(&self.#f_ident).ok()
+				vec![app_route::RouteInfo {
+					pattern: #url_route.to_string(),
+					host: #host,
+					methods: vec![#(#route_methods.to_string()),*],
+					query: #route_query,
+				}]
 			}
 		}
-	});
+	};
+
+	let matched_pattern = match nested_field {
+		Some(ref nested_field) => {
+			let f_ident = &nested_field.ident;
+			let mount_prefix = if url_route == "/" {
+				""
+			} else {
+				url_route.as_str()
+			};
+
+			quote! { format!("{}{}", #mount_prefix, self.#f_ident.__matched_pattern()) }
+		}
+		None => quote! { #url_route.to_string() },
+	};
+
+	let method_matching = if methods.is_empty() {
+		quote! { let _ = method; }
+	} else {
+		let route_methods = &methods;
+
+		quote! {
+			if let Some(method) = method {
+				const METHODS: &[&str] = &[#(#route_methods),*];
 
+				if !METHODS.contains(&method) {
+					return Err(RouteParseErr::MethodNotAllowed {
+						allowed: METHODS.iter().map(|m| m.to_string()).collect(),
+					});
+				}
+			}
+		}
+	};
+
+	// The nested route is given the rest of the path and the whole query
+	// string, and its path and query are appended to the parent's
+	let (nested_host, nested_path, nested_query, nested_canonical_query) = match nested_field {
+		Some(ref nested_field) => {
+			let f_ident = &nested_field.ident;
+
+			(
+				quote! { let nested_host = host; },
+				quote! { + &self.#f_ident.path() },
+				quote! { self.#f_ident.query_string() },
+				quote! {
+					self.#f_ident
+						.canonical_string()
+						.split_once('?')
+						.map(|(_, query)| query.to_string())
+				},
+			)
+		}
+		None => (quote! {}, quote! {}, quote! {}, quote! {}),
+	};
+
+	let encoded_query_fields = query_fields
+		.iter()
+		.map(|f| query_field_to_string(f, false))
+		.chain(Some(nested_query).filter(|q| !q.is_empty()));
 	let encoded_query_fields = quote! {
 		#(
-			#query_field_to_string_statements
+			#encoded_query_fields
 		),*
 	};
 
-	let struct_constructor = match (
-		route_field_parsers.is_empty(),
-		query_field_parsers.is_empty(),
-	) {
-		(true, true) => quote! {
-			#name {}
-		},
-		(true, false) => quote! {
-			#name {
-				#query_field_parsers
-			}
-		},
-		(false, true) => quote! {
-			#name {
-				#route_field_parsers
-			}
-		},
-		(false, false) => quote! {
-			#name {
-				#route_field_parsers,
-				#query_field_parsers
+	let canonical_query_fields = query_fields
+		.iter()
+		.map(|f| query_field_to_string(f, true))
+		.chain(Some(nested_canonical_query).filter(|q| !q.is_empty()));
+	let canonical_query_fields = quote! {
+		#(
+			#canonical_query_fields
+		),*
+	};
+
+	let nested_field_assignment = nested_field.iter().map(|f| {
+		let f_ident = &f.ident;
+		let f_ty = &f.ty;
+
+		quote! {
+			#f_ident: {
+				let nested_start = captures.get(captures.len() - 1).unwrap().start();
+				<#f_ty as app_route::AppRoute>::from_parts(method, nested_host, &app_path[nested_start..], limits)?
 			}
-		},
+		}
+	});
+
+	let field_assignments = route_field_assignments
+		.chain(nested_field_assignment)
+		.chain(query_field_assignments);
+
+	let struct_constructor = quote! {
+		#name {
+			#(#field_assignments),*
+		}
 	};
 
 	let app_route_impl = quote! {
 		impl #impl_generics app_route::AppRoute for #name #ty_generics #where_clause {
 
 			fn path_pattern() -> String {
-				#route_regex_str.to_string()
+				#path_pattern
 			}
 
-			fn query_string(&self) -> Option<String> {
-				use app_route::serde_qs as qs;
+			fn routes() -> Vec<app_route::RouteInfo> {
+				#routes_impl
+			}
+
+			fn from_parts(method: Option<&str>, host: Option<&str>, app_path: &str, limits: &app_route::ParseLimits) -> Result<Self, app_route::RouteParseErr> {
+				use app_route::RouteParseErr;
 
+				app_route::lazy_static! {
+					static ref ROUTE_REGEX: app_route::Regex = app_route::Regex::new(#route_regex_str).expect("Failed to compile regex");
+				}
+
+				let question_pos = app_path.find('?');
+				let just_path = &app_path[..(question_pos.unwrap_or_else(|| app_path.len()))];
+
+				let query_string = question_pos.map(|question_pos| {
+					let mut query_string = &app_path[question_pos..];
+
+					if query_string.starts_with('?') {
+						query_string = &query_string[1..];
+					}
+
+					query_string
+				});
+
+				limits.check(app_path, query_string)?;
+
+				let captures = (*ROUTE_REGEX).captures(just_path).ok_or(RouteParseErr::NoMatches)?;
+
+				#nested_host
+				#host_matching
+				#method_matching
+
+				Ok(#struct_constructor)
+			}
+
+			#host_impl
+
+			fn __matched_pattern(&self) -> String {
+				#matched_pattern
+			}
+
+			fn path(&self) -> String {
+				format!(
+					#format_str,
+					#format_args
+				) #nested_path
+			}
+
+			fn query_string(&self) -> Option<String> {
 				// TODO - Remove duplicates because
 				//        there could be multiple fields with
 				//        a #[query] attribute that have common fields
 
 				// TODO - can this be done with an on-stack array?
 				let encoded_queries: Vec<Option<String>> = vec![#encoded_query_fields];
-				let filtered: Vec<_> = encoded_queries.into_iter().filter_map(std::convert::identity).collect();
+				app_route::query::join(encoded_queries)
+			}
 
-				if !filtered.is_empty() {
-					Some(filtered.join("&"))
-				} else {
-					None
+			fn canonical_string(&self) -> String {
+				let encoded_queries: Vec<Option<String>> = vec![#canonical_query_fields];
+
+				let path_and_query = match app_route::query::join(encoded_queries) {
+					Some(query) => format!("{}?{}", self.path(), app_route::query::canonicalize(&query)),
+					None => self.path(),
+				};
+
+				match self.host() {
+					Some(host) => format!("//{}{}", host, path_and_query),
+					None => path_and_query,
 				}
 			}
 		}
@@ -381,18 +1280,9 @@ pub fn app_route_derive(input: TokenStream) -> TokenStream {
 		impl #impl_generics std::fmt::Display for #name #ty_generics #where_clause {
 			fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
 				if let Some(query) = self.query_string() {
-					let path = format!(
-						#format_str,
-						#format_args
-					);
-
-					write!(f, "{}?{}", path, query)
+					write!(f, "{}?{}", self.path(), query)
 				} else {
-					write!(
-						f,
-						#format_str,
-						#format_args
-					)
+					write!(f, "{}", self.path())
 				}
 			}
 		}
@@ -401,42 +1291,173 @@ pub fn app_route_derive(input: TokenStream) -> TokenStream {
 			type Err = app_route::RouteParseErr;
 
 			fn from_str(app_path: &str) -> Result<Self, Self::Err> {
-				use app_route::serde_qs as qs;
-				use app_route::RouteParseErr;
+				<Self as app_route::AppRoute>::from_str_with_limits(app_path, &app_route::ParseLimits::default())
+			}
+		}
+	};
 
-				app_route::lazy_static! {
-					static ref ROUTE_REGEX: app_route::Regex = app_route::Regex::new(#route_regex_str).expect("Failed to compile regex");
+	let conversion_impls = conversion_impls(name, &generics);
+	let serde_impls = serde_impls(name, &generics, &input.attrs);
+
+	let openapi_impl = if cfg!(feature = "openapi") {
+		let path_parameters = openapi_path_parameters(&path_capture_names, &route_fields);
+		let query_types = query_fields.iter().map(|f| &f.ty);
+		let route_methods = &methods;
+
+		let operations = match nested_field {
+			Some(ref nested_field) => {
+				let nested_ty = &nested_field.ty;
+				let mount_prefix = if url_route == "/" {
+					""
+				} else {
+					url_route.as_str()
+				};
+
+				quote! {
+					<#nested_ty as app_route::openapi::OpenApi>::operations()
+						.into_iter()
+						.map(|operation| {
+							operation.mount(
+								&app_route::openapi::openapi_path(#mount_prefix),
+								&parameters,
+								&[#(#route_methods),*],
+							)
+						})
+						.collect()
 				}
+			}
+			None => quote! {
+				vec![app_route::openapi::Operation {
+					path: app_route::openapi::openapi_path(#url_route),
+					methods: vec![#(#route_methods.to_string()),*],
+					parameters,
+				}]
+			},
+		};
 
-				let question_pos = app_path.find('?');
-				let just_path = &app_path[..(question_pos.unwrap_or_else(|| app_path.len()))];
+		quote! {
+			impl #impl_generics app_route::openapi::OpenApi for #name #ty_generics #where_clause {
+				fn operations() -> Vec<app_route::openapi::Operation> {
+					#[allow(unused_mut)]
+					let mut parameters: Vec<app_route::openapi::Value> = vec![#(#path_parameters),*];
+					#(
+						parameters.extend(app_route::openapi::query_parameters::<#query_types>());
+					)*
+
+					#operations
+				}
+			}
+		}
+	} else {
+		quote! {}
+	};
 
-				let captures = (*ROUTE_REGEX).captures(just_path).ok_or(RouteParseErr::NoMatches)?;
+	let typescript_impl = if cfg!(feature = "typescript") {
+		let name_str = name.to_string();
+		let params = path_capture_names.iter().map(|param| {
+			let field = route_fields
+				.iter()
+				.find(|f| f.ident.as_ref().map(|i| i == param).unwrap_or(false))
+				.expect("route params have matching fields");
+			let type_name = type_name(&field.ty);
 
-				let query_string = question_pos.map(|question_pos| {
-					let mut query_string = &app_path[question_pos..];
+			quote! {
+				app_route::typescript::Param::new(#param, #type_name)
+			}
+		});
 
-					if query_string.starts_with('?') {
-						query_string = &query_string[1..];
-					}
+		let queries = query_fields.iter().map(|field| {
+			let ty = &field.ty;
+			let field_name = field.ident.as_ref().unwrap().to_string();
+			let QueryOptions {
+				spaces,
+				skip_defaults,
+			} = get_query_options(field);
 
-					query_string
-				});
+			let defaults = if skip_defaults == SkipDefaults::Always {
+				let inner_ty = option_inner_type(ty);
 
-				Ok(#struct_constructor)
+				quote! {
+					Some(app_route::query::to_string(&<#inner_ty as Default>::default(), #spaces).unwrap_or_default())
+				}
+			} else {
+				quote! { None }
+			};
+
+			quote! {
+				app_route::typescript::QueryField::new::<#ty>(#field_name, #spaces, #defaults)
+			}
+		});
+
+		let helpers = match nested_field {
+			Some(ref nested_field) => {
+				let nested_ty = &nested_field.ty;
+				let mount_prefix = if url_route == "/" {
+					""
+				} else {
+					url_route.as_str()
+				};
+
+				quote! {
+					<#nested_ty as app_route::typescript::TypeScript>::helpers()
+						.into_iter()
+						.map(|helper| helper.mount(#name_str, #mount_prefix, &params, &queries))
+						.collect()
+				}
+			}
+			None => quote! {
+				vec![app_route::typescript::Helper {
+					name: #name_str.to_string(),
+					scope: vec![],
+					pattern: #url_route.to_string(),
+					params,
+					queries,
+				}]
+			},
+		};
+
+		quote! {
+			impl #impl_generics app_route::typescript::TypeScript for #name #ty_generics #where_clause {
+				fn helpers() -> Vec<app_route::typescript::Helper> {
+					let params: Vec<app_route::typescript::Param> = vec![#(#params),*];
+					let queries: Vec<app_route::typescript::QueryField> = vec![#(#queries),*];
+
+					#helpers
+				}
 			}
 		}
+	} else {
+		quote! {}
 	};
 
-	let impl_wrapper = syn::Ident::new(
-		&format!("_IMPL_APPROUTE_FOR_{}", name.to_string()),
-		proc_macro2::Span::call_site(),
-	);
+	let endpoint_impl = if cfg!(feature = "reqwest") {
+		let method = methods.first().map(String::as_str).unwrap_or("GET");
+		let request = request.map(|ty| quote! { #ty }).unwrap_or(quote! { () });
+		let response = response.map(|ty| quote! { #ty }).unwrap_or(quote! { () });
+
+		quote! {
+			impl #impl_generics app_route::client::Endpoint for #name #ty_generics #where_clause {
+				const METHOD: &'static str = #method;
+				type Request = #request;
+				type Response = #response;
+			}
+		}
+	} else {
+		quote! {}
+	};
 
 	let out = quote! {
-		const #impl_wrapper: () = {
+		#builder
+
+		const _: () = {
 			extern crate app_route;
 			#app_route_impl
+			#conversion_impls
+			#serde_impls
+			#query_mut_impls
+			#openapi_impl
+			#typescript_impl
+			#endpoint_impl
 		};
 	};
 
@@ -0,0 +1,147 @@
+//! `#[derive(Pagination)]`, implementing `Pagination` and `CursorPagination`
+//! for query structs.
+
+use crate::{field_is_option, get_attr_options, get_struct_fields, lit_to_string, option_inner_type};
+use quote::quote;
+
+struct PaginationOptions {
+	limit: String,
+	offset: String,
+	default_limit: Option<u64>,
+	cursor: Option<String>,
+}
+
+fn get_pagination_options(attrs: &[syn::Attribute]) -> PaginationOptions {
+	let mut options = PaginationOptions {
+		limit: "limit".to_string(),
+		offset: "offset".to_string(),
+		default_limit: None,
+		cursor: None,
+	};
+
+	for (key, value) in get_attr_options("pagination", attrs) {
+		match key.as_str() {
+			"limit" => options.limit = lit_to_string(&value, &key),
+			"offset" => options.offset = lit_to_string(&value, &key),
+			"cursor" => options.cursor = Some(lit_to_string(&value, &key)),
+			"default_limit" => match value {
+				Some(syn::Lit::Int(int_lit)) => options.default_limit = Some(int_lit.value()),
+				_ => panic!("Expected an integer literal for `default_limit`"),
+			},
+			_ => panic!("Unknown #[pagination] option `{}`", key),
+		}
+	}
+
+	options
+}
+
+fn find_field<'a>(fields: &'a [syn::Field], name: &str) -> Option<&'a syn::Field> {
+	fields
+		.iter()
+		.find(|f| f.ident.as_ref().is_some_and(|ident| ident == name))
+}
+
+/// Implements `Pagination` when the struct has the `limit` and `offset`
+/// fields, and `CursorPagination` when a `cursor` field is named.
+pub fn derive_pagination(input: &syn::DeriveInput) -> proc_macro2::TokenStream {
+	let name = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+	let fields = get_struct_fields(&input.data);
+	let options = get_pagination_options(&input.attrs);
+
+	let mut impls = quote! {};
+
+	let limit_field = find_field(&fields, &options.limit);
+	let offset_field = find_field(&fields, &options.offset);
+
+	match (limit_field, offset_field) {
+		(Some(limit_field), Some(offset_field)) => {
+			let limit_ident = &limit_field.ident;
+			let offset_ident = &offset_field.ident;
+
+			let limit = match (field_is_option(limit_field), options.default_limit) {
+				(true, Some(default_limit)) => quote! {
+					self.#limit_ident.map(|limit| limit as u64).unwrap_or(#default_limit)
+				},
+				(true, None) => panic!(
+					"#[pagination(default_limit = ...)] is required when `{}` is an Option",
+					options.limit
+				),
+				(false, _) => quote! { self.#limit_ident as u64 },
+			};
+
+			// Offsets too large for a narrower field saturate rather than wrap.
+			let offset_ty = option_inner_type(&offset_field.ty);
+			let narrowed = quote! {
+				<#offset_ty as std::convert::TryFrom<u64>>::try_from(offset)
+					.unwrap_or(<#offset_ty>::MAX)
+			};
+
+			let (offset, set_offset) = if field_is_option(offset_field) {
+				(
+					quote! { self.#offset_ident.map(|offset| offset as u64).unwrap_or(0) },
+					quote! {
+						self.#offset_ident = if offset == 0 { None } else { Some(#narrowed) };
+					},
+				)
+			} else {
+				(
+					quote! { self.#offset_ident as u64 },
+					quote! { self.#offset_ident = #narrowed; },
+				)
+			};
+
+			impls.extend(quote! {
+				#[allow(clippy::unnecessary_cast)]
+				impl #impl_generics app_route::pagination::Pagination for #name #ty_generics #where_clause {
+					fn limit(&self) -> u64 {
+						#limit
+					}
+
+					fn offset(&self) -> u64 {
+						#offset
+					}
+
+					fn set_offset(&mut self, offset: u64) {
+						#set_offset
+					}
+				}
+			});
+		}
+		(None, Some(_)) if options.cursor.is_none() => {
+			panic!("Pagination query has no `{}` field", options.limit)
+		}
+		(_, None) if options.cursor.is_none() => {
+			panic!("Pagination query has no `{}` field", options.offset)
+		}
+		_ => {}
+	}
+
+	if let Some(ref cursor) = options.cursor {
+		let cursor_field = find_field(&fields, cursor)
+			.unwrap_or_else(|| panic!("Pagination query has no `{}` field", cursor));
+		let cursor_ident = &cursor_field.ident;
+
+		let set_cursor = if field_is_option(cursor_field) {
+			quote! { self.#cursor_ident = cursor.map(Into::into); }
+		} else {
+			quote! { self.#cursor_ident = cursor.unwrap_or("").into(); }
+		};
+
+		impls.extend(quote! {
+			impl #impl_generics app_route::pagination::CursorPagination for #name #ty_generics #where_clause {
+				fn set_cursor(&mut self, cursor: Option<&str>) {
+					#set_cursor
+				}
+			}
+		});
+	}
+
+	quote! {
+		const _: () = {
+			extern crate app_route;
+
+			#impls
+		};
+	}
+}
@@ -0,0 +1,538 @@
+//! `#[derive(AppRoute)]` for enums, which act as routers: each variant
+//! wraps another route type, and parsing tries the variants from the most
+//! to the least specific, unless `#[route(priority = N)]` says otherwise.
+
+use crate::{
+	capture_names, conversion_impls, get_attr_options, get_container_options, get_string_attr,
+	prefix_to_regex, serde_impls, template_to_route, type_name,
+};
+use quote::quote;
+
+/// A variant of an enum router, e.g. `Users(UsersRoute)` or
+/// `Users { org_id: u64, route: UsersRoute }` when the prefix has params.
+struct RouterVariant {
+	ident: syn::Ident,
+	child_ty: syn::Type,
+	/// The field holding the child route, for struct variants.
+	child_field: Option<syn::Ident>,
+	/// The fields holding the prefix params, for struct variants.
+	prefix_fields: Vec<syn::Ident>,
+	/// The types of `prefix_fields`.
+	prefix_field_types: Vec<syn::Type>,
+	/// From `#[route(priority = N)]`. Variants with a higher priority are
+	/// tried first, regardless of specificity.
+	priority: u64,
+}
+
+fn get_variant_priority(variant: &syn::Variant) -> u64 {
+	let mut priority = 0;
+
+	for (key, value) in get_attr_options("route", &variant.attrs) {
+		match (key.as_str(), value) {
+			("priority", Some(syn::Lit::Int(int_lit))) => priority = int_lit.value(),
+			("priority", _) => panic!("Expected an integer literal for `priority`"),
+			_ => panic!(
+				"Unknown #[route] option `{}` on variant `{}`, router variants only take `priority`",
+				key, variant.ident
+			),
+		}
+	}
+
+	priority
+}
+
+fn get_router_variants(data_enum: &syn::DataEnum, prefix_params: &[String]) -> Vec<RouterVariant> {
+	data_enum
+		.variants
+		.iter()
+		.map(|variant| match variant.fields {
+			syn::Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+				if !prefix_params.is_empty() {
+					panic!(
+						"Variant `{}` must be a struct variant with fields for the route prefix params {:?}",
+						variant.ident, prefix_params
+					);
+				}
+
+				RouterVariant {
+					ident: variant.ident.clone(),
+					child_ty: fields.unnamed[0].ty.clone(),
+					child_field: None,
+					prefix_fields: vec![],
+					prefix_field_types: vec![],
+					priority: get_variant_priority(variant),
+				}
+			}
+			syn::Fields::Named(ref fields) => {
+				let (prefix_fields, child_fields): (Vec<_>, Vec<_>) = fields
+					.named
+					.iter()
+					.partition(|f| prefix_params.contains(&f.ident.as_ref().unwrap().to_string()));
+
+				if prefix_fields.len() != prefix_params.len() || child_fields.len() != 1 {
+					panic!(
+						"Variant `{}` must have a field for each route prefix param {:?}, and one field for the nested route",
+						variant.ident, prefix_params
+					);
+				}
+
+				RouterVariant {
+					ident: variant.ident.clone(),
+					child_ty: child_fields[0].ty.clone(),
+					child_field: child_fields[0].ident.clone(),
+					prefix_fields: prefix_fields.iter().map(|f| f.ident.clone().unwrap()).collect(),
+					prefix_field_types: prefix_fields.iter().map(|f| f.ty.clone()).collect(),
+					priority: get_variant_priority(variant),
+				}
+			}
+			_ => panic!(
+				"Variant `{}` must wrap a single route, e.g. `{}(SomeRoute)`",
+				variant.ident, variant.ident
+			),
+		})
+		.collect()
+}
+
+pub fn derive_enum_router(
+	input: &syn::DeriveInput,
+	data_enum: &syn::DataEnum,
+) -> proc_macro2::TokenStream {
+	let name = &input.ident;
+
+	// Generic routers cache their variant order by `TypeId`
+	let mut generics = input.generics.clone();
+	if !generics.params.is_empty() {
+		generics
+			.make_where_clause()
+			.predicates
+			.push(syn::parse_quote!(Self: 'static));
+	}
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+	if get_string_attr("route", &input.attrs).is_some() {
+		panic!("Enum routers take a #[route_prefix(\"/prefix\")] attribute instead of #[route]");
+	}
+
+	if get_container_options(&input.attrs).builder {
+		panic!("#[app_route(builder)] is only supported for structs");
+	}
+
+	let prefix = get_string_attr("route_prefix", &input.attrs).map(|prefix| {
+		template_to_route(&prefix)
+			.expect("Could not convert the URI template in the route_prefix attribute")
+	});
+
+	let (prefix_regex_str, prefix_format_str) = match prefix {
+		Some(ref prefix) => prefix_to_regex(prefix)
+			.expect("Could not convert route_prefix attribute to a valid regex"),
+		None => ("^".to_string(), "".to_string()),
+	};
+
+	let mount_prefix = match prefix {
+		Some(ref prefix) if prefix != "/" => prefix.clone(),
+		_ => "".to_string(),
+	};
+
+	let prefix_params = capture_names(&prefix_regex_str);
+	let variants = get_router_variants(data_enum, &prefix_params);
+
+	// Every route of the later variant would be shadowed by the earlier one
+	for (i, variant) in variants.iter().enumerate() {
+		let child_ty = &variant.child_ty;
+		let child_ty = quote!(#child_ty).to_string();
+
+		for later in &variants[i + 1..] {
+			let later_ty = &later.child_ty;
+
+			if quote!(#later_ty).to_string() == child_ty {
+				panic!(
+					"Variants `{}` and `{}` both wrap `{}`, so `{}` can never match",
+					variant.ident,
+					later.ident,
+					type_name(&later.child_ty),
+					later.ident
+				);
+			}
+		}
+	}
+
+	let child_types: Vec<_> = variants.iter().map(|v| v.child_ty.clone()).collect();
+	let child_types = &child_types;
+
+	// Binds the child route of a variant to `child`, and its prefix params to
+	// their own names
+	let variant_patterns: Vec<_> = variants
+		.iter()
+		.map(|v| {
+			let ident = &v.ident;
+			let prefix_fields = &v.prefix_fields;
+
+			match v.child_field {
+				Some(ref child_field) => quote! {
+					#name::#ident { #child_field: child, #(#prefix_fields),* }
+				},
+				None => quote! {
+					#name::#ident(child)
+				},
+			}
+		})
+		.collect();
+
+	// Binds only the child route of a variant to `child`
+	let child_patterns: Vec<_> = variants
+		.iter()
+		.map(|v| {
+			let ident = &v.ident;
+
+			match v.child_field {
+				Some(ref child_field) => quote! {
+					#name::#ident { #child_field: child, .. }
+				},
+				None => quote! {
+					#name::#ident(child)
+				},
+			}
+		})
+		.collect();
+
+	let prefix_paths: Vec<_> = variants
+		.iter()
+		.map(|v| {
+			let prefix_fields = &v.prefix_fields;
+			let prefix_values = &v.prefix_fields;
+
+			quote! {
+				format!(#prefix_format_str, #(#prefix_fields = #prefix_values),*)
+			}
+		})
+		.collect();
+
+	let variant_patterns = &variant_patterns;
+	let child_patterns = &child_patterns;
+	let prefix_paths = &prefix_paths;
+
+	let priorities: Vec<_> = variants.iter().map(|v| v.priority).collect();
+	let priorities = &priorities;
+
+	let variant_parsers = variants.iter().enumerate().map(|(index, v)| {
+		let ident = &v.ident;
+		let child_ty = &v.child_ty;
+		let prefix_fields = &v.prefix_fields;
+		let prefix_field_strs: Vec<_> = prefix_fields.iter().map(|f| f.to_string()).collect();
+
+		let parse_child = quote! {
+			<#child_ty as app_route::AppRoute>::from_parts(method, host, child_path, limits)
+		};
+
+		let result = match v.child_field {
+			Some(ref child_field) => quote! {
+				#parse_child.and_then(|child| {
+					Ok(#name::#ident {
+						#child_field: child,
+						#(
+							#prefix_fields: prefix_captures[#prefix_field_strs].parse().map_err(|e| {
+								RouteParseErr::ParamParseErr(std::string::ToString::to_string(&e))
+							})?
+						),*
+					})
+				})
+			},
+			None => quote! {
+				#parse_child.map(#name::#ident)
+			},
+		};
+
+		quote! {
+			#index => match #result {
+				Ok(route) => {
+					let specificity = app_route::registry::Specificity::of(&route.__matched_pattern());
+
+					if best.as_ref().map_or(true, |best| specificity > best.2) {
+						best = Some((route, variant.priority, specificity));
+					}
+				}
+				Err(RouteParseErr::NoMatches) => {}
+				Err(RouteParseErr::MethodNotAllowed { allowed }) => {
+					for method in allowed {
+						if !allowed_methods.contains(&method) {
+							allowed_methods.push(method);
+						}
+					}
+				}
+				Err(e) => {
+					if let RouteParseErr::NoMatches = error {
+						error = e;
+					}
+				}
+			},
+		}
+	});
+
+	let variant_routes = quote! {
+		#((#priorities, <#child_types as app_route::AppRoute>::routes())),*
+	};
+
+	// A static can't name the type parameters, so generic routers cache
+	// their order by type instead
+	let variant_order = if input.generics.params.is_empty() {
+		quote! {
+			app_route::lazy_static! {
+				static ref VARIANT_ORDER: Vec<app_route::registry::RankedVariant> =
+					app_route::registry::variant_order(&[#variant_routes]);
+			}
+
+			&VARIANT_ORDER[..]
+		}
+	} else {
+		quote! {
+			app_route::registry::cached_variant_order::<Self, _>(|| vec![#variant_routes])
+		}
+	};
+
+	let app_route_impl = quote! {
+		impl #impl_generics #name #ty_generics #where_clause {
+			// The variants in the order they are tried, by priority and then
+			// specificity
+			#[doc(hidden)]
+			fn __app_route_variant_order() -> &'static [app_route::registry::RankedVariant] {
+				#variant_order
+			}
+		}
+
+		impl #impl_generics app_route::AppRoute for #name #ty_generics #where_clause {
+			fn path_pattern() -> String {
+				let patterns: Vec<String> = vec![
+					#(<#child_types as app_route::AppRoute>::path_pattern()),*
+				];
+
+				app_route::pattern::nest(#prefix_regex_str, &patterns)
+			}
+
+			fn routes() -> Vec<app_route::RouteInfo> {
+				let child_routes = vec![#(<#child_types as app_route::AppRoute>::routes()),*];
+
+				app_route::registry::merge_routes(Self::__app_route_variant_order(), child_routes)
+					.into_iter()
+					.map(|route| route.mount(#mount_prefix, None, &[]))
+					.collect()
+			}
+
+			fn from_parts(method: Option<&str>, host: Option<&str>, app_path: &str, limits: &app_route::ParseLimits) -> Result<Self, app_route::RouteParseErr> {
+				use app_route::RouteParseErr;
+
+				app_route::lazy_static! {
+					static ref PREFIX_REGEX: app_route::Regex = app_route::Regex::new(concat!(#prefix_regex_str, "(/.*)$")).expect("Failed to compile regex");
+				}
+
+				let question_pos = app_path.find('?');
+				let just_path = &app_path[..(question_pos.unwrap_or_else(|| app_path.len()))];
+				let query_string = question_pos.map(|question_pos| &app_path[(question_pos + 1)..]);
+
+				limits.check(app_path, query_string)?;
+
+				let prefix_captures = (*PREFIX_REGEX).captures(just_path).ok_or(RouteParseErr::NoMatches)?;
+				let rest_start = prefix_captures.get(prefix_captures.len() - 1).unwrap().start();
+				let child_path = &app_path[rest_start..];
+
+				// If none of the variants match, the first error other than
+				// `NoMatches` is the most relevant one. Variants which only
+				// reject the method are merged into a single `MethodNotAllowed`.
+				let mut error = RouteParseErr::NoMatches;
+				let mut allowed_methods: Vec<String> = Vec::new();
+
+				// The most specific match so far, with its priority. A router
+				// variant's routes can be of any specificity, so a later
+				// variant may still have a more specific match.
+				let mut best: Option<(Self, u64, app_route::registry::Specificity)> = None;
+
+				for variant in Self::__app_route_variant_order() {
+					if let Some((_, priority, ref specificity)) = best {
+						if variant.priority < priority || variant.specificity.as_ref() <= Some(specificity) {
+							break;
+						}
+					}
+
+					match variant.index {
+						#(#variant_parsers)*
+						_ => unreachable!(),
+					}
+				}
+
+				if let Some((route, _, _)) = best {
+					return Ok(route);
+				}
+
+				match error {
+					RouteParseErr::NoMatches if !allowed_methods.is_empty() => {
+						Err(RouteParseErr::MethodNotAllowed { allowed: allowed_methods })
+					}
+					error => Err(error),
+				}
+			}
+
+			fn path(&self) -> String {
+				match self {
+					#(
+						#variant_patterns => format!("{}{}", #prefix_paths, child.path())
+					),*
+				}
+			}
+
+			fn host(&self) -> Option<String> {
+				match self {
+					#(
+						#child_patterns => child.host()
+					),*
+				}
+			}
+
+			fn __matched_pattern(&self) -> String {
+				let child_pattern = match self {
+					#(
+						#child_patterns => child.__matched_pattern()
+					),*
+				};
+
+				format!("{}{}", #mount_prefix, child_pattern)
+			}
+
+			fn query_string(&self) -> Option<String> {
+				match self {
+					#(
+						#child_patterns => child.query_string()
+					),*
+				}
+			}
+
+			fn canonical_string(&self) -> String {
+				match self {
+					#(
+						#variant_patterns => app_route::uri::mount_canonical(&#prefix_paths, child.host(), child.canonical_string())
+					),*
+				}
+			}
+		}
+
+		impl #impl_generics std::fmt::Display for #name #ty_generics #where_clause {
+			fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+				if let Some(query) = self.query_string() {
+					write!(f, "{}?{}", self.path(), query)
+				} else {
+					write!(f, "{}", self.path())
+				}
+			}
+		}
+
+		impl #impl_generics std::str::FromStr for #name #ty_generics #where_clause {
+			type Err = app_route::RouteParseErr;
+
+			fn from_str(app_path: &str) -> Result<Self, Self::Err> {
+				<Self as app_route::AppRoute>::from_str_with_limits(app_path, &app_route::ParseLimits::default())
+			}
+		}
+	};
+
+	let conversion_impls = conversion_impls(name, &generics);
+	let serde_impls = serde_impls(name, &generics, &input.attrs);
+
+	let openapi_impl = if cfg!(feature = "openapi") {
+		// Each variant mounts its child's operations under the prefix, with
+		// the prefix params typed by its own fields
+		let mounted_operations = variants.iter().map(|v| {
+			let child_ty = &v.child_ty;
+			let prefix_parameters = prefix_params.iter().map(|param| {
+				let index = v
+					.prefix_fields
+					.iter()
+					.position(|f| f == param)
+					.expect("prefix params have matching fields");
+				let type_name = type_name(&v.prefix_field_types[index]);
+
+				quote! {
+					app_route::openapi::path_parameter(#param, #type_name)
+				}
+			});
+
+			quote! {
+				let parameters: Vec<app_route::openapi::Value> = vec![#(#prefix_parameters),*];
+				operations.extend(
+					<#child_ty as app_route::openapi::OpenApi>::operations()
+						.into_iter()
+						.map(|operation| operation.mount(&prefix, &parameters, &[])),
+				);
+			}
+		});
+
+		quote! {
+			impl #impl_generics app_route::openapi::OpenApi for #name #ty_generics #where_clause {
+				fn operations() -> Vec<app_route::openapi::Operation> {
+					let prefix = app_route::openapi::openapi_path(#mount_prefix);
+					let mut operations = Vec::new();
+
+					#(
+						#mounted_operations
+					)*
+
+					operations
+				}
+			}
+		}
+	} else {
+		quote! {}
+	};
+
+	let typescript_impl = if cfg!(feature = "typescript") {
+		let mounted_helpers = variants.iter().map(|v| {
+			let child_ty = &v.child_ty;
+			let variant_str = v.ident.to_string();
+			let prefix_params = prefix_params.iter().map(|param| {
+				let index = v
+					.prefix_fields
+					.iter()
+					.position(|f| f == param)
+					.expect("prefix params have matching fields");
+				let type_name = type_name(&v.prefix_field_types[index]);
+
+				quote! {
+					app_route::typescript::Param::new(#param, #type_name)
+				}
+			});
+
+			quote! {
+				let params: Vec<app_route::typescript::Param> = vec![#(#prefix_params),*];
+				helpers.extend(
+					<#child_ty as app_route::typescript::TypeScript>::helpers()
+						.into_iter()
+						.map(|helper| helper.mount(#variant_str, #mount_prefix, &params, &[])),
+				);
+			}
+		});
+
+		quote! {
+			impl #impl_generics app_route::typescript::TypeScript for #name #ty_generics #where_clause {
+				fn helpers() -> Vec<app_route::typescript::Helper> {
+					let mut helpers = Vec::new();
+
+					#(
+						#mounted_helpers
+					)*
+
+					helpers
+				}
+			}
+		}
+	} else {
+		quote! {}
+	};
+
+	quote! {
+		const _: () = {
+			extern crate app_route;
+			#app_route_impl
+			#conversion_impls
+			#serde_impls
+			#openapi_impl
+			#typescript_impl
+		};
+	}
+}